@@ -1,201 +1,770 @@
-use crate::{egui_printer, egui_println};
+use crate::log_t;
+use crate::export::{self, ExportFormat};
+use crate::filter::SourceFilter;
+use crate::history::{self, DuplicateAction, History, HistoryEntry};
+use crate::http::{self, HttpConfig};
+use crate::manifest::{self, EntryStatus, Manifest, ManifestEntry, MANIFEST_FILE};
+use crate::mirrors::{self, MirrorConfig};
+use crate::resolve::{self, AlbumPage, FilePage, Resolution, UrlKind};
 
-use spider::website::Website;
-use spider::tokio;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
-use fake_user_agent::get_firefox_rua;
-use scraper::{Html, Selector};
-use reqwest;
+use tokio::sync::watch;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::{time::Duration, time::Instant};
-use futures::stream::{self, StreamExt};
+use futures::future;
+use futures::stream::{self, Stream, StreamExt};
+use sha2::{Digest, Sha256};
 
-const CONCURRENT_LIMIT: usize = 8;
-const DEFAULT_DOWNLOAD_DIR: &str = "no_title";
+pub const DEFAULT_DOWNLOAD_CONCURRENT_LIMIT: usize = 8;
+pub const DEFAULT_ANALYZE_CONCURRENT_LIMIT: usize = 8;
+pub const DEFAULT_MAX_PAGES: usize = 2000; // 分析阶段最多请求的页面数（专辑页 + 文件页）
+pub(crate) const DEFAULT_DOWNLOAD_DIR: &str = "no_title";
 const DEFAULT_BASE_DIR: &str = ".";
-const RETRY_COUNT: usize = 3;  // 单文件重试次数
-const MIN_SPEED_BPS: u64 = 0; // 最低下载速率（1KB/s）
+pub const DEFAULT_RETRY_COUNT: usize = 3;  // 单文件重试次数
+const MIN_SPEED_BPS: Option<u64> = None; // 最低下载速率（如 Some(1000) 即 1KB/s），None 表示不限制
 const MIN_FILE_SIZE: u64 = 200; // 最小文件大小阈值（200B）
+const PAUSE_POLL: Duration = Duration::from_millis(200); // 暂停时检查是否继续的间隔
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
 pub enum BunkrSpiderState {
+    #[default]
     Idle,
     Analyzing,
+    /// 分析完成，等待用户勾选要下载的文件
+    Reviewing,
     Downloading,
+    /// 所有文件都下载成功（试运行结束时也是这个状态）
     Finished,
+    /// 部分文件下载失败
+    PartiallyCompleted,
+    /// 所有文件都下载失败
+    Failed,
+    /// 下载被停止，还有文件没有完成
+    Cancelled,
 }
 
-#[derive(Clone)]
+impl BunkrSpiderState {
+    /// 下载结束后的状态：`failed` 个失败，共 `total` 个文件；
+    /// `analyzed_all` 为 false 表示专辑没能载入或有页面没有分析出文件
+    pub fn after_downloads(total: usize, failed: usize, stopped: bool, analyzed_all: bool) -> BunkrSpiderState {
+        if stopped && (failed > 0 || !analyzed_all) {
+            BunkrSpiderState::Cancelled
+        } else if failed == total && (total > 0 || !analyzed_all) {
+            BunkrSpiderState::Failed
+        } else if failed == 0 && analyzed_all {
+            BunkrSpiderState::Finished
+        } else {
+            BunkrSpiderState::PartiallyCompleted
+        }
+    }
+
+    /// 是否已经结束，不会再自行变化
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            BunkrSpiderState::Finished
+                | BunkrSpiderState::PartiallyCompleted
+                | BunkrSpiderState::Failed
+                | BunkrSpiderState::Cancelled
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct BunkrSpiderInformation {
     pub total_sources: Option<usize>,
     pub downloaded_sources: Option<usize>,
+    /// 分析阶段已发现的页面数（专辑页 + 文件页）
+    pub pages_discovered: usize,
+    /// 分析阶段已请求并解析完的页面数
+    pub pages_parsed: usize,
+    /// 分析阶段请求失败、没有解析出文件或因停止而没有请求的页面数
+    pub pages_failed: usize,
     pub state: BunkrSpiderState,
+    /// 上次下载中失败的文件
+    pub failed: Vec<FailedSource>,
+}
+
+/// 下载失败的文件及最后一次尝试的错误
+#[derive(Clone, Debug)]
+pub struct FailedSource {
+    pub source: Source,
+    pub error: String,
+}
+
+/// 爬虫与界面共享的进度信息。所有修改都经由 watch 通道写入，
+/// 读者只取快照，不会因为有人正在读而丢掉状态切换或计数
+#[derive(Clone, Default)]
+pub struct SharedInfo {
+    sender: Arc<watch::Sender<BunkrSpiderInformation>>,
+}
+
+impl SharedInfo {
+    pub fn new() -> SharedInfo {
+        SharedInfo::default()
+    }
+
+    /// 当前信息的快照
+    pub fn get(&self) -> BunkrSpiderInformation {
+        self.sender.borrow().clone()
+    }
+
+    pub fn update(&self, modify: impl FnOnce(&mut BunkrSpiderInformation)) {
+        self.sender.send_modify(modify);
+    }
+
+    /// 订阅之后的每次修改，可用 `changed().await` 等待
+    pub fn subscribe(&self) -> watch::Receiver<BunkrSpiderInformation> {
+        self.sender.subscribe()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Source {
+    pub title: String,
+    pub url: String,
+    /// 文件所在页面，下载时作为 Referer
+    pub page_url: String,
+    pub resolution: Resolution,
+    /// HEAD 请求得到的大小，未探测或服务器未返回时为 None
+    pub size: Option<u64>,
+}
+
+impl Source {
+    /// 保存到本地时使用的文件名
+    pub fn file_name(&self) -> String {
+        BunkrSpider::sanitize_filename(&self.title)
+    }
+}
+
+/// 一个文件下载（或从历史中复用）成功后的结果
+#[derive(Clone, Debug)]
+pub struct Downloaded {
+    pub size: u64,
+    pub sha256: String,
+    pub path: PathBuf,
+    /// 复用了历史中已有的这个文件（跳过或硬链接）
+    pub reused_from: Option<PathBuf>,
+}
+
+/// 一次同步（`sync`）发现的变化，都是文件标题
+#[derive(Clone, Debug, Default)]
+pub struct SyncReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// 只重试失败文件时的可选设置
+#[derive(Clone, Debug, Default)]
+pub struct RetryOptions {
+    /// 同时下载的文件数，None 时沿用当前设置
+    pub concurrency: Option<usize>,
+    /// 改用另一个 CDN 主机名下载
+    pub switch_cdn: bool,
+}
+
+/// 停止和暂停标志，可以交给其他任务在爬虫运行时控制它
+#[derive(Clone, Default)]
+pub struct SpiderControl {
+    should_stop: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+}
+
+impl SpiderControl {
+    pub fn stop(&self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.should_stop.load(Ordering::Relaxed)
+    }
+
+    /// 暂停后不再请求新的页面，正在下载的文件也会在下一块数据前停下
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// 暂停期间一直等待，停止时立即返回
+    async fn wait_while_paused(&self) {
+        while self.is_paused() && !self.is_stopped() {
+            tokio::time::sleep(PAUSE_POLL).await;
+        }
+    }
+
+    /// 一直等到收到停止信号，用来中断不检查标志的工作
+    pub async fn wait_until_stopped(&self) {
+        while !self.is_stopped() {
+            tokio::time::sleep(PAUSE_POLL).await;
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.should_stop.store(false, Ordering::Relaxed);
+        self.paused.store(false, Ordering::Relaxed);
+    }
 }
 
 #[derive(Clone)]
 pub struct BunkrSpider {
-    website: Website,
     client: reqwest::Client,
+    mirrors: MirrorConfig,
+    /// 单文件链接是否下载到以文件名命名的文件夹
+    single_file_folder: bool,
+    max_pages: usize,
+    analyze_concurrency: usize,
+    download_concurrency: usize,
+    /// 单个文件失败后的重试次数
+    retries: usize,
+    filter: SourceFilter,
+    /// 被筛选条件排除的文件数
+    filtered_out: usize,
+    /// 规范化后的专辑（或单文件）链接
+    album_url: String,
+    /// 分析时至少成功载入了一个专辑页（或单文件页）
+    loaded: bool,
+    /// 专辑页中列出的全部文件页，`sync` 据此判断哪些文件已从专辑中删除
+    file_pages: Vec<String>,
+    /// 专辑的每一页都已载入，`file_pages` 没有遗漏
+    listing_complete: bool,
+    /// 单文件链接且不建文件夹：直接下载到下载目录
+    no_album_folder: bool,
     title: String,
-    sources: Vec<(String, String)>,
+    sources: Vec<Source>,
     base_dir: String,
-    should_stop: Arc<AtomicBool>,
-    info: Arc<tokio::sync::RwLock<BunkrSpiderInformation>>,
+    /// 下载历史，用于跨专辑识别已下载过的文件
+    history: Option<Arc<tokio::sync::Mutex<History>>>,
+    duplicate_action: DuplicateAction,
+    control: SpiderControl,
+    info: SharedInfo,
 }
 
 
+impl Default for BunkrSpider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BunkrSpider{
     pub fn new() -> BunkrSpider{
         BunkrSpider { 
-            website: (Website::new("")), 
-            client: (reqwest::Client::new()), 
+            client: (http::build_client(&HttpConfig::default()).unwrap_or_default()), 
+            mirrors: MirrorConfig::default(),
+            single_file_folder: false,
+            max_pages: DEFAULT_MAX_PAGES,
+            analyze_concurrency: DEFAULT_ANALYZE_CONCURRENT_LIMIT,
+            download_concurrency: DEFAULT_DOWNLOAD_CONCURRENT_LIMIT,
+            retries: DEFAULT_RETRY_COUNT,
+            filter: SourceFilter::default(),
+            filtered_out: 0,
+            album_url: String::new(),
+            loaded: false,
+            file_pages: Vec::new(),
+            listing_complete: false,
+            no_album_folder: false,
             title: (String::new()), 
-            sources: (Vec::<(String, String)>::new()),
+            sources: (Vec::<Source>::new()),
             base_dir: (DEFAULT_BASE_DIR.to_string()),
-            should_stop: Arc::new(AtomicBool::new(false)),
-            info: Arc::new(tokio::sync::RwLock::new(BunkrSpiderInformation {
-                total_sources: None,
-                downloaded_sources: None,
-                state: BunkrSpiderState::Idle,
-            })),
+            history: None,
+            duplicate_action: DuplicateAction::default(),
+            control: SpiderControl::default(),
+            info: SharedInfo::new(),
         }
     }
 
-    /// Construct a BunkrSpider that shares the provided `info` Arc.
-    /// This allows the UI to read the same internal state while the spider
-    /// holds its own lock during long-running operations.
-    pub fn with_info(info: Arc<tokio::sync::RwLock<BunkrSpiderInformation>>) -> BunkrSpider {
+    /// Construct a BunkrSpider that shares the provided `info`.
+    /// This allows the UI to read the same state while the spider
+    /// is busy with long-running operations.
+    pub fn with_info(info: SharedInfo) -> BunkrSpider {
         let mut s = BunkrSpider::new();
         s.info = info;
         s
     }
 
-    pub async fn run(&mut self, base_dir: String, url: String) -> Arc<tokio::sync::RwLock<BunkrSpiderInformation>> {
+    /// 替换 HTTP 配置并重建客户端，爬取和下载都会使用新的客户端
+    pub fn set_http_config(&mut self, config: &HttpConfig) -> Result<(), Box<dyn std::error::Error>> {
+        self.client = http::build_client(config)?;
+        Ok(())
+    }
+
+    /// 直接使用已经建好的 HTTP 客户端
+    pub fn set_client(&mut self, client: reqwest::Client) {
+        self.client = client;
+    }
+
+    /// 设置下载历史；为 None 时不记录也不查重
+    pub fn set_history(&mut self, history: Option<Arc<tokio::sync::Mutex<History>>>) {
+        self.history = history;
+    }
+
+    pub fn set_duplicate_action(&mut self, action: DuplicateAction) {
+        self.duplicate_action = action;
+    }
+
+    pub fn set_mirrors(&mut self, mirrors: MirrorConfig) {
+        self.mirrors = mirrors;
+    }
+
+    pub fn set_single_file_folder(&mut self, enabled: bool) {
+        self.single_file_folder = enabled;
+    }
+
+    pub fn set_max_pages(&mut self, max_pages: usize) {
+        self.max_pages = max_pages.max(1);
+    }
+
+    /// 分析阶段同时请求的文件页数量
+    pub fn set_analyze_concurrency(&mut self, concurrency: usize) {
+        self.analyze_concurrency = concurrency.max(1);
+    }
+
+    /// 同时下载的文件数量
+    pub fn set_download_concurrency(&mut self, concurrency: usize) {
+        self.download_concurrency = concurrency.max(1);
+    }
+
+    /// 单个文件失败后最多重试的次数，0 表示不重试
+    pub fn set_retries(&mut self, retries: usize) {
+        self.retries = retries;
+    }
+
+    /// 设置筛选条件，正则无效时返回错误且不修改原有条件
+    pub fn set_filter(&mut self, filter: SourceFilter) -> Result<(), regex::Error> {
+        filter.compile()?;
+        self.filter = filter;
+        Ok(())
+    }
+
+    pub async fn run(&mut self, base_dir: String, url: String) -> SharedInfo {
+        let file_pages = self.prepare(base_dir, url).await;
+        let resolved: Vec<Source> = self.resolve_file_pages(file_pages).collect().await;
+        self.sources.extend(resolved);
+        self.apply_filter().await;
+
+        let total = self.sources.len();
+        self.info.update(|info| {
+            info.total_sources = Some(total);
+            info.downloaded_sources = Some(0);
+        });
+
+        self.info.clone()
+    }
+
+    /// 边分析边下载：每解析出一个文件就交给下载阶段，两个阶段各自受并发上限约束。
+    /// 结束后 `sources`、计数和 manifest 与先 `run` 再 `download_all` 一致。
+    pub async fn run_pipelined(&mut self, base_dir: String, url: String) -> Result<(), Box<dyn std::error::Error>> {
+        let file_pages = self.prepare(base_dir, url).await;
+        if !self.loaded {
+            self.finish_not_loaded();
+            return Ok(());
+        }
+
+        // 总数随筛选后交给下载阶段的文件逐个增加
+        self.info.update(|info| {
+            info.total_sources = Some(0);
+            info.downloaded_sources = Some(0);
+        });
+
+        let info = self.info.clone();
+        let client = self.client.clone();
+        let filter = if self.filter.is_empty() { None } else { self.filter.compile().ok().map(Arc::new) };
+        let needs_size = self.filter.needs_size();
+        let filtered_out = Arc::new(AtomicUsize::new(0));
+        let mut discovered = Vec::new();
+
+        let sources = stream::iter(std::mem::take(&mut self.sources))
+            .chain(self.resolve_file_pages(file_pages))
+            .filter_map(|mut source| {
+                let client = client.clone();
+                let filter = filter.clone();
+                let filtered_out = Arc::clone(&filtered_out);
+                async move {
+                    let Some(filter) = filter else {
+                        return Some(source);
+                    };
+                    if needs_size {
+                        source.size = Self::probe_size(&client, &source).await;
+                    }
+                    if filter.matches(&source) {
+                        Some(source)
+                    } else {
+                        filtered_out.fetch_add(1, Ordering::Relaxed);
+                        None
+                    }
+                }
+            })
+            .inspect(|source| {
+                info.update(|info| {
+                    if let Some(total) = info.total_sources {
+                        info.total_sources = Some(total + 1);
+                    }
+                });
+                discovered.push(source.clone());
+            });
+
+        let results = self.download_sources(sources).await;
+        self.sources.extend(discovered);
+        self.filtered_out = filtered_out.load(Ordering::Relaxed);
+        self.finish_downloads(results).await;
+        Ok(())
+    }
+
+    /// 规范化链接并确定需要解析的文件页。
+    /// 专辑只抓取专辑页本身，返回其中的文件页；单文件链接直接解析并放入 `sources`。
+    async fn prepare(&mut self, base_dir: String, url: String) -> Vec<String> {
         self.base_dir = base_dir;
+        self.info.update(|info| info.state = BunkrSpiderState::Analyzing);
 
         let website_name = url.trim().split('?').next().unwrap().to_string();
-        self.website = Website::new(&website_name);
-        self.website.with_user_agent(Some(get_firefox_rua()));
-
-        if let Ok(mut info) = self.info.try_write(){
-            info.state = BunkrSpiderState::Analyzing;
-        }
-
-        // 在持有锁期间定期释放，允许GUI读取
-        self.website.scrape().await;
-        // 释放锁，让GUI能读取状态
-        tokio::task::yield_now().await;
-
-        let meta_type_selector = Selector::parse("head > meta:nth-child(5)").unwrap();
-        let meta_title_selector = Selector::parse("head > meta:nth-child(6)").unwrap();
-        let meta_title_png_selector = Selector::parse("head > meta:nth-child(7)").unwrap();
-        let img_selector = Selector::parse(
-            "body > main:nth-child(11) > figure > img.max-h-full.w-auto.object-cover.relative.z-20"
-        ).unwrap();
-
-        for (page_count, page) in self.website.get_pages().unwrap().iter().enumerate() {
-            let html = page.get_html();
-            let document = Html::parse_document(&html);
-
-            // type
-            if let Some(meta_type_elem) = document.select(&meta_type_selector).next() {
-                let _type = meta_type_elem.value().attr("content").unwrap_or("");
-                    if _type.is_empty() || _type == "website" {
-                        continue;
-                    }
+        let website_name = mirrors::normalize_url(&self.client, &website_name, &self.mirrors).await;
+        self.album_url = website_name.clone();
 
-                    // title
-                    if let Some(meta_title_elem) = document.select(&meta_title_selector).next() {
-                        let _title = meta_title_elem.value().attr("content").unwrap_or("");
-                        // 记录album标题
-                        if self.title.is_empty() && _type == "album"{
-                            let _t = Self::sanitize_filename(_title);
-
-                            if !_t.is_empty(){
-                                egui_println!("album title: {}", _t);
-                                self.title = _t;
-                            }
-                        }
-                        if _type == "album"{
-                            continue;
-                        }
+        match UrlKind::of(&website_name) {
+            UrlKind::File => {
+                self.analyze_single_file(&website_name).await;
+                Vec::new()
+            }
+            UrlKind::Album => self.collect_album_items(&website_name).await,
+        }
+    }
 
-                        let title_etc = 
-                            ".".to_string() + _title.rsplit('.').next().unwrap_or("");
-
-                        if title_etc == ".jpg" || title_etc == ".png" || title_etc == ".gif" || title_etc == ".jpeg" {
-                            // image
-                            if let Some(img_elem) = document.select(&img_selector).next() {
-                                let _img_src = img_elem.value().attr("src").unwrap_or("");
-                                if _img_src.is_empty() {
-                                    continue;
-                                }
-                                let real_link =  _img_src.to_string();
-
-                                // egui_println!("Image --- title: {}\nlink: {}", _title, real_link);
-                                self.sources.push((_title.to_string(), real_link));
-                            }
-                        }
-                        // video
-                        else{
-                        // title_png
-                        if let Some(meta_title_elem) = document.select(&meta_title_png_selector).next() {
-                            let _title_png = meta_title_elem.value().attr("content").unwrap_or("");
-                            let mut real_link =  Self::remove_all_extensions_after_last_slash(
-                            _title_png
-                                .replace("/thumbs", "")
-                                .replace("https://i-", "https://")
-                            );
-                            real_link += &title_etc;
-
-                            // egui_println!("Video --- title: {}\nlink: {}", _title, real_link);
-                            self.sources.push((_title.to_string(), real_link));
-                        }
-                    }
+    /// 只抓取专辑页（含分页），返回其中列出的文件页，不跟随其他链接
+    async fn collect_album_items(&mut self, album_url: &str) -> Vec<String> {
+        let mut album_pages = vec![album_url.to_string()];
+        let mut items: Vec<String> = Vec::new();
+        let mut fetched_pages = 0;
+        Self::add_page_progress(&self.info, 1, 0);
+
+        let mut complete = true;
+
+        while let Some(page_url) = album_pages.get(fetched_pages).cloned() {
+            self.control.wait_while_paused().await;
+            if self.control.is_stopped() || fetched_pages >= self.max_pages {
+                if self.control.is_stopped() {
+                    Self::add_failed_pages(&self.info, album_pages.len() - fetched_pages);
                 }
+                complete = false;
+                break;
             }
-            
-            // 每处理几个页面就释放一次锁，让 GUI 能更新状态
-            if page_count % 5 == 0 {
-                tokio::task::yield_now().await;
+            fetched_pages += 1;
+
+            let html = match http::fetch_html(&self.client, &page_url, album_url).await {
+                Ok(html) => html,
+                Err(e) => {
+                    Self::add_page_progress(&self.info, 0, 1);
+                    Self::add_failed_pages(&self.info, 1);
+                    log_t!("log.album_page_failed", url = page_url, error = e);
+                    complete = false;
+                    continue;
+                }
+            };
+            let album_page = AlbumPage::parse(&html, &page_url);
+            self.loaded = true;
+
+            // 记录album标题
+            if self.title.is_empty() {
+                let _t = Self::sanitize_filename(&album_page.title);
+
+                if !_t.is_empty(){
+                    log_t!("log.album_title", title = _t);
+                    self.title = _t;
+                }
+            }
+
+            let known = items.len() + album_pages.len();
+            for item in album_page.items {
+                if !items.contains(&item) {
+                    items.push(item);
+                }
+            }
+            for page in album_page.pages {
+                if !album_pages.contains(&page) {
+                    album_pages.push(page);
+                }
             }
+            Self::add_page_progress(&self.info, items.len() + album_pages.len() - known, 1);
         }
 
-        if let Ok(mut info) = self.info.try_write(){
-            info.total_sources = Some(self.sources.len());
-            info.downloaded_sources = Some(0);
+        self.file_pages = items.clone();
+        self.listing_complete = complete && self.loaded;
+
+        let budget = self.max_pages.saturating_sub(fetched_pages);
+        if items.len() > budget {
+            log_t!("log.album_truncated", items = items.len(), budget = budget, max_pages = self.max_pages);
+            items.truncate(budget);
         }
+        self.info.update(|info| info.pages_discovered = fetched_pages + items.len());
+        log_t!("log.file_pages_found", items = items.len(), pages = fetched_pages);
+        items
+    }
 
-        Arc::clone(&self.info)
+    /// 并发请求并解析文件页，按专辑中的顺序产出解析成功的文件
+    fn resolve_file_pages(&self, file_pages: Vec<String>) -> impl Stream<Item = Source> + 'static {
+        let client = self.client.clone();
+        let control = self.control.clone();
+        let info = self.info.clone();
+        let referer = self.album_url.clone();
+
+        stream::iter(file_pages)
+            .map(move |page_url| {
+                let client = client.clone();
+                let control = control.clone();
+                let info = info.clone();
+                let referer = referer.clone();
+
+                async move {
+                    control.wait_while_paused().await;
+                    if control.is_stopped() {
+                        Self::add_failed_pages(&info, 1);
+                        return None;
+                    }
+                    let source = match http::fetch_html(&client, &page_url, &referer).await {
+                        Ok(html) => Self::resolve_file_page(&client, &html, page_url).await,
+                        Err(e) => {
+                            log_t!("log.file_page_failed", url = page_url, error = e);
+                            None
+                        }
+                    };
+                    Self::add_page_progress(&info, 0, 1);
+                    if source.is_none() {
+                        Self::add_failed_pages(&info, 1);
+                    }
+                    source
+                }
+            })
+            .buffered(self.analyze_concurrency)
+            .filter_map(future::ready)
     }
 
-    fn remove_all_extensions_after_last_slash(url: String) -> String {
-        if let Some(last_slash) = url.rfind('/') {
-            let (base, filename) = url.split_at(last_slash + 1);
-            
-            if let Some(first_dot) = filename.find('.') {
-                format!("{}{}", base, &filename[..first_dot])
-            } else {
-                url.to_string()
+    /// 单个文件页（/f/、/v/、/i/）：只请求这一页，不做整站爬取
+    async fn analyze_single_file(&mut self, page_url: &str) {
+        Self::add_page_progress(&self.info, 1, 0);
+        let html = http::fetch_html(&self.client, page_url, "").await;
+        Self::add_page_progress(&self.info, 0, 1);
+
+        let html = match html {
+            Ok(html) => html,
+            Err(e) => {
+                Self::add_failed_pages(&self.info, 1);
+                log_t!("log.file_page_failed", url = page_url, error = e);
+                return;
             }
+        };
+
+        let Some(source) = Self::resolve_file_page(&self.client, &html, page_url.to_string()).await else {
+            Self::add_failed_pages(&self.info, 1);
+            return;
+        };
+        self.loaded = true;
+        self.file_pages = vec![page_url.to_string()];
+        self.listing_complete = true;
+
+        if self.single_file_folder {
+            let stem = source.title
+                .rsplit_once('.')
+                .map_or(source.title.as_str(), |(stem, _)| stem);
+            self.title = Self::sanitize_filename(stem);
         } else {
-            if let Some(first_dot) = url.find('.') {
-                url[..first_dot].to_string()
-            } else {
-                url.to_string()
+            self.no_album_folder = true;
+        }
+
+        self.sources.push(source);
+    }
+
+    fn add_page_progress(info: &SharedInfo, discovered: usize, parsed: usize) {
+        info.update(|info| {
+            info.pages_discovered += discovered;
+            info.pages_parsed += parsed;
+        });
+    }
+
+    fn add_failed_pages(info: &SharedInfo, count: usize) {
+        if count > 0 {
+            info.update(|info| info.pages_failed += count);
+        }
+    }
+
+    /// 专辑已载入，且每个发现的页面都分析出了文件
+    fn analyzed_all(&self) -> bool {
+        self.loaded && self.info.get().pages_failed == 0
+    }
+
+    async fn resolve_file_page(client: &reqwest::Client, html: &str, page_url: String) -> Option<Source> {
+        let file_page = FilePage::parse(html, &page_url);
+        if file_page.title.is_empty() {
+            log_t!("log.no_file_on_page", url = page_url);
+            return None;
+        }
+
+        match resolve::resolve_media(client, &file_page, &page_url).await {
+            Some((url, resolution)) => {
+                if resolution == Resolution::ThumbnailGuess {
+                    log_t!("log.guessed_from_thumbnail", title = file_page.title, url = url);
+                }
+                Some(Source {
+                    title: file_page.title,
+                    url,
+                    page_url,
+                    resolution,
+                    size: None,
+                })
+            }
+            None => {
+                log_t!("log.unresolved_media", url = page_url);
+                None
             }
         }
     }
 
-    fn get_download_dir(&self) -> String {
-        let mut path = PathBuf::from(&self.base_dir);
-        if !path.exists() || !path.is_dir() {
-            path = PathBuf::from(DEFAULT_BASE_DIR);
+    pub fn sources(&self) -> &[Source] {
+        &self.sources
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// 规范化（换到可用镜像）后的专辑链接
+    pub fn album_url(&self) -> &str {
+        &self.album_url
+    }
+
+    /// 上次分析是否成功载入了专辑页（或单文件页）；为 false 时 `sources` 为空不代表专辑是空的
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn filtered_out(&self) -> usize {
+        self.filtered_out
+    }
+
+    /// 载入已经分析好的专辑，之后可直接 `download_all`
+    pub fn load_album(&mut self, album_url: String, title: String, sources: Vec<Source>, base_dir: String) {
+        self.info.update(|info| {
+            info.total_sources = Some(sources.len());
+            info.downloaded_sources = Some(0);
+        });
+        self.album_url = album_url;
+        self.loaded = true;
+        self.title = title;
+        self.sources = sources;
+        self.base_dir = base_dir;
+    }
+
+    /// 用 HEAD 请求探测每个文件的大小（服务器给出 Content-Length 时）
+    pub async fn probe_sizes(&mut self) {
+        let client = self.client.clone();
+        let unknown: Vec<(usize, Source)> = self.sources
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, source)| source.size.is_none())
+            .collect();
+        let sizes: Vec<(usize, Option<u64>)> = stream::iter(unknown)
+            .map(|(index, source)| {
+                let client = client.clone();
+                async move { (index, Self::probe_size(&client, &source).await) }
+            })
+            .buffer_unordered(self.analyze_concurrency)
+            .collect()
+            .await;
+
+        for (index, size) in sizes {
+            self.sources[index].size = size;
+        }
+    }
+
+    async fn probe_size(client: &reqwest::Client, source: &Source) -> Option<u64> {
+        http::with_referer(client.head(&source.url), &source.page_url)
+            .send()
+            .await
+            .ok()
+            .filter(|r| r.status().is_success())
+            .and_then(|r| r.content_length())
+            .filter(|len| *len > 0)
+    }
+
+    /// 按筛选条件去掉不需要的文件，有大小条件时先探测大小
+    async fn apply_filter(&mut self) {
+        if self.filter.is_empty() {
+            return;
+        }
+        let Ok(filter) = self.filter.compile() else {
+            return;
+        };
+        if self.filter.needs_size() {
+            self.probe_sizes().await;
         }
 
+        let before = self.sources.len();
+        self.sources.retain(|source| filter.matches(source));
+        self.filtered_out = before - self.sources.len();
+        log_t!("log.filter_kept", kept = self.sources.len(), total = before);
+    }
+
+    /// 进入挑选阶段，之后由 `retain_sources` + `download_all` 继续
+    pub fn begin_review(&self) {
+        self.info.update(|info| info.state = BunkrSpiderState::Reviewing);
+    }
+
+    /// 只保留被勾选的文件，`selected` 与 `sources()` 一一对应
+    pub fn retain_sources(&mut self, selected: &[bool]) {
+        let mut keep = selected.iter().copied();
+        self.sources.retain(|_| keep.next().unwrap_or(false));
+
+        let total = self.sources.len();
+        self.info.update(|info| {
+            info.total_sources = Some(total);
+            info.downloaded_sources = Some(0);
+        });
+    }
+
+    /// 专辑没能载入：不建目录、不写 manifest，直接以失败结束
+    pub fn finish_not_loaded(&self) {
+        log_t!("log.album_not_loaded", url = self.album_url);
+        self.info.update(|info| info.state = BunkrSpiderState::Failed);
+    }
+
+    /// 试运行结束：不下载，只把分析结果留给 `export`
+    pub fn finish_dry_run(&self) {
+        let state = if self.loaded { BunkrSpiderState::Finished } else { BunkrSpiderState::Failed };
+        self.info.update(|info| info.state = state);
+    }
+
+    /// 按指定格式导出当前的文件列表
+    pub fn export(&self, format: ExportFormat) -> String {
+        export::export(&self.sources, &self.get_download_dir(), format)
+    }
+
+    /// 导出时建议的文件名
+    pub fn export_file_name(&self, format: ExportFormat) -> String {
+        export::file_name(&self.title, format)
+    }
+
+    /// 专辑文件夹（下载目录 + 专辑标题），不建文件夹的单文件链接就是下载目录本身；
+    /// 下载目录不存在时在下载前创建，不会换到别处
+    pub fn get_download_dir(&self) -> String {
+        let mut path = if self.base_dir.is_empty() {
+            PathBuf::from(DEFAULT_BASE_DIR)
+        } else {
+            PathBuf::from(&self.base_dir)
+        };
+
+        if self.no_album_folder {
+            return path.to_string_lossy().to_string();
+        }
         if self.title.is_empty() {
             path = path.join(DEFAULT_DOWNLOAD_DIR);
         } else {
@@ -205,100 +774,438 @@ impl BunkrSpider{
         path.to_string_lossy().to_string()
     }
 
-    pub async fn download_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let download_dir = self.get_download_dir();
+    /// 重新分析专辑，与目录中的 manifest 比较，只下载新出现的文件。
+    /// 文件按文件 ID 比较，专辑换了镜像域名也能对上；专辑页没有全部载入时不报告删除
+    pub async fn sync(&mut self, base_dir: String, url: String) -> Result<SyncReport, Box<dyn std::error::Error>> {
+        self.run(base_dir, url.clone()).await;
+        if self.is_stopped() {
+            return Err("Task stopped".into());
+        }
+        if !self.loaded {
+            return Err(format!("could not load {}", url).into());
+        }
 
-        let _ = fs::create_dir_all(&download_dir).await;
+        let download_dir = self.get_download_dir();
+        let previous = Manifest::load(Path::new(&download_dir)).await.map(|m| m.entries).unwrap_or_default();
+        let downloaded: Vec<&ManifestEntry> = previous.iter().filter(|e| e.status.is_done()).collect();
+        let downloaded_ids: HashSet<String> = downloaded.iter().map(|e| history::file_id(&e.page_url)).collect();
 
-        egui_println!("find {} sources", self.sources.len());
+        // 清单之外的文件只有大小与已知大小一致才算下载过，中断留下的残缺文件照样重新下载
+        let selected: Vec<bool> = self.sources
+            .iter()
+            .map(|source| {
+                let recorded = previous
+                    .iter()
+                    .find(|e| e.file_name == source.file_name())
+                    .and_then(|e| e.size);
+                let complete_on_disk = source.size.or(recorded).is_some_and(|size| {
+                    std::fs::metadata(Path::new(&download_dir).join(source.file_name()))
+                        .is_ok_and(|metadata| metadata.is_file() && metadata.len() == size)
+                });
+                !downloaded_ids.contains(&history::file_id(&source.page_url)) && !complete_on_disk
+            })
+            .collect();
+        let removed: Vec<String> = if self.listing_complete {
+            let listed: HashSet<String> = self.file_pages.iter().map(|page| history::file_id(page)).collect();
+            downloaded
+                .iter()
+                .filter(|e| !listed.contains(&history::file_id(&e.page_url)))
+                .map(|e| e.title.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.retain_sources(&selected);
+        let added: Vec<String> = self.sources.iter().map(|source| source.title.clone()).collect();
 
-        if let Ok(mut info) = self.info.try_write(){
-            info.state = BunkrSpiderState::Downloading;
+        for title in &added {
+            log_t!("log.album_added", title = title);
+        }
+        for title in &removed {
+            log_t!("log.album_removed", title = title);
         }
+        self.download_all().await?;
 
-        let client = Arc::new(self.client.clone());
-        let should_stop = Arc::clone(&self.should_stop);
-        let info = Arc::clone(&self.info);
+        Ok(SyncReport { added, removed })
+    }
 
-        // 创建所有权版本的 sources 向量，避免生命周期问题
-        let sources_owned: Vec<_> = self.sources
+    /// 从专辑目录的 manifest 载入上次的结果，失败的文件之后可用 `retry_failed` 重试，
+    /// 返回失败文件的数量
+    pub async fn load_failed(&mut self, album_dir: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let manifest = Manifest::load(album_dir)
+            .await
+            .map_err(|e| format!("cannot read {} in {}: {}", MANIFEST_FILE, album_dir.display(), e))?;
+
+        // manifest 不记录解析方式，重试时都当作直接的媒体链接
+        let to_source = |entry: &ManifestEntry| Source {
+            title: entry.title.clone(),
+            url: entry.url.clone(),
+            page_url: entry.page_url.clone(),
+            resolution: Resolution::MediaSource,
+            size: entry.size,
+        };
+        let failed: Vec<FailedSource> = manifest.entries
             .iter()
-            .map(|(name, url)| (name.clone(), url.clone()))
+            .filter(|entry| entry.status == EntryStatus::Failed)
+            .map(|entry| FailedSource {
+                source: to_source(entry),
+                error: entry.error.clone().unwrap_or_default(),
+            })
             .collect();
+        let sources: Vec<Source> = manifest.entries.iter().map(to_source).collect();
+        let downloaded = sources.len() - failed.len();
+        let count = failed.len();
 
-        let downloads = stream::iter(sources_owned.into_iter().enumerate())
-            .map(move |(index, (name, url))| {
+        let base_dir = album_dir.parent().map_or(DEFAULT_BASE_DIR.to_string(), |dir| dir.to_string_lossy().to_string());
+        let title = album_dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        self.load_album(manifest.album_url, title, sources, base_dir);
+        self.info.update(|info| {
+            info.downloaded_sources = Some(downloaded);
+            info.state = BunkrSpiderState::after_downloads(info.total_sources.unwrap_or(0), count, false, true);
+            info.failed = failed;
+        });
+        Ok(count)
+    }
+
+    /// 只重新下载上次失败的文件，目录、manifest 和计数都接着当前专辑
+    pub async fn retry_failed(&mut self, options: RetryOptions) -> Result<(), Box<dyn std::error::Error>> {
+        let mut failed: Vec<Source> = self.info.get().failed.into_iter().map(|f| f.source).collect();
+        if failed.is_empty() {
+            log_t!("log.no_failed_files");
+            return Ok(());
+        }
+        if options.switch_cdn {
+            for source in failed.iter_mut() {
+                if let Some(alternative) = mirrors::cdn_alternatives(&source.url, &self.mirrors).into_iter().next() {
+                    source.url = alternative;
+                }
+            }
+        }
+
+        self.control.clear();
+        let concurrency = self.download_concurrency;
+        if let Some(retry_concurrency) = options.concurrency {
+            self.set_download_concurrency(retry_concurrency);
+        }
+        log_t!("log.retrying_failed", count = failed.len());
+
+        let results = self.download_sources(stream::iter(failed)).await;
+        self.download_concurrency = concurrency;
+        self.finish_downloads(results).await;
+        Ok(())
+    }
+
+    pub async fn download_all(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.loaded {
+            self.finish_not_loaded();
+            return Ok(());
+        }
+        log_t!("log.sources_found", count = self.sources.len());
+
+        let results = self.download_sources(stream::iter(self.sources.clone())).await;
+        self.finish_downloads(results).await;
+        Ok(())
+    }
+
+    async fn download_sources(&self, sources: impl Stream<Item = Source>) -> Vec<(Source, Result<Downloaded, String>)> {
+        self.download_stream(sources).collect().await
+    }
+
+    /// 下载阶段本身：每完成一个文件产出一个结果，结束后需调用 `finish_downloads`
+    pub(crate) fn download_stream<S: Stream<Item = Source>>(&self, sources: S)
+        -> impl Stream<Item = (Source, Result<Downloaded, String>)> + use<S> {
+        let download_dir = self.get_download_dir();
+
+        // 目录建不起来时每个文件都以此失败，而不是悄悄下载到别处
+        let dir_error = std::fs::create_dir_all(&download_dir)
+            .err()
+            .map(|e| format!("cannot create {}: {}", download_dir, e));
+
+        self.info.update(|info| info.state = BunkrSpiderState::Downloading);
+
+        let client = Arc::new(self.client.clone());
+        let mirrors = Arc::new(self.mirrors.clone());
+        let control = self.control.clone();
+        let info = self.info.clone();
+        let history = self.history.clone();
+        let duplicate_action = self.duplicate_action;
+        let album_url = self.album_url.clone();
+        let concurrency = self.download_concurrency;
+        let retries = self.retries;
+
+        sources
+            .enumerate()
+            .map(move |(index, source)| {
                 let client = Arc::clone(&client);
-                let should_stop = Arc::clone(&should_stop);
-                let info = Arc::clone(&info);
+                let mirrors = Arc::clone(&mirrors);
+                let control = control.clone();
+                let info = info.clone();
+                let history = history.clone();
+                let album_url = album_url.clone();
                 let dir = download_dir.clone();
+                let dir_error = dir_error.clone();
 
                 async move {
-                    if should_stop.load(Ordering::Relaxed) {
-                        return (name.clone(), Err("Task stopped".to_string()));
+                    control.wait_while_paused().await;
+                    if control.is_stopped() {
+                        return (source, Err("Task stopped".to_string()));
+                    }
+                    if let Some(e) = dir_error {
+                        return (source, Err(e));
+                    }
+                    if let Some(history) = &history
+                        && duplicate_action != DuplicateAction::Download
+                        && let Some(downloaded) = Self::reuse_copy(history, duplicate_action, &dir, &source).await
+                    {
+                        Self::count_downloaded(&info);
+                        if downloaded.reused_from.as_ref() != Some(&downloaded.path) {
+                            Self::remember(history, duplicate_action, &album_url, &source, &mut downloaded.clone()).await;
+                        }
+                        return (source, Ok(downloaded));
                     }
-                    let result = Self::download_with_retry(
-                        &client, dir, url, 
-                        &name, index, should_stop, 
-                        Arc::clone(&info)).await;
-                    (name, result)
+
+                    let mut result = Self::download_with_retry(
+                        &client, &mirrors, dir, &source, 
+                        index, retries, control, 
+                        info.clone()).await;
+                    if let Some(history) = &history
+                        && let Ok(downloaded) = &mut result
+                    {
+                        Self::remember(history, duplicate_action, &album_url, &source, downloaded).await;
+                    }
+                    (source, result)
                 }
             })
-            .buffer_unordered(CONCURRENT_LIMIT);
+            .buffer_unordered(concurrency)
+    }
 
-        let results: Vec<_> = downloads.collect().await;
+    /// 历史中已有同一文件时按设置跳过或硬链接，返回 None 表示仍需下载
+    async fn reuse_copy(
+        history: &tokio::sync::Mutex<History>,
+        action: DuplicateAction,
+        download_dir: &str,
+        source: &Source,
+    ) -> Option<Downloaded> {
+        let existing = history.lock().await.find_copy(source).cloned()?;
+        let target = Path::new(download_dir).join(source.file_name());
+        let target = std::path::absolute(&target).unwrap_or(target);
 
-        for (name, result) in results {
-            match result {
-                Ok(_) => egui_println!("✓ downloaded: {}", name),
-                Err(e) => egui_println!("✗ failed: {} - {}", name, e),
+        let path = if action == DuplicateAction::Hardlink && target != existing.path {
+            if let Err(e) = Self::link_or_copy(&existing.path, &target).await {
+                log_t!("log.link_failed", title = source.title, path = existing.path.display(), error = e);
+                return None;
+            }
+            log_t!("log.already_linked", title = source.title, path = existing.path.display());
+            target
+        } else {
+            log_t!("log.already_skipped", title = source.title, path = existing.path.display());
+            existing.path.clone()
+        };
+
+        Some(Downloaded {
+            size: existing.size,
+            sha256: existing.sha256,
+            path,
+            reused_from: Some(existing.path),
+        })
+    }
+
+    /// 记录到下载历史；内容与已有文件完全相同时换成硬链接节省空间
+    async fn remember(
+        history: &tokio::sync::Mutex<History>,
+        action: DuplicateAction,
+        album_url: &str,
+        source: &Source,
+        downloaded: &mut Downloaded,
+    ) {
+        if let Ok(path) = std::path::absolute(&downloaded.path) {
+            downloaded.path = path;
+        }
+        let mut history = history.lock().await;
+        if action == DuplicateAction::Hardlink
+            && downloaded.reused_from.is_none()
+            && let Some(existing) = history.find_by_hash(&downloaded.sha256, &downloaded.path)
+        {
+            let existing = existing.path.clone();
+            match Self::link_or_copy(&existing, &downloaded.path).await {
+                Ok(()) => {
+                    log_t!("log.duplicate_linked", path = existing.display(), title = source.title);
+                    downloaded.reused_from = Some(existing);
+                }
+                Err(e) => log_t!("log.duplicate_link_failed", title = source.title, error = e),
             }
         }
 
-        egui_println!("all downloads attempted.");
-        if let Ok(mut info) = self.info.try_write(){
-            info.state = BunkrSpiderState::Finished;
+        history.record(HistoryEntry {
+            album_url: album_url.to_string(),
+            page_url: source.page_url.clone(),
+            file_id: history::file_id(&source.page_url),
+            media_url: source.url.clone(),
+            title: source.title.clone(),
+            size: downloaded.size,
+            sha256: downloaded.sha256.clone(),
+            path: downloaded.path.clone(),
+            downloaded_at: manifest::unix_now(),
+        }).await;
+    }
+
+    /// 在 `target` 建立指向 `existing` 的硬链接（先建临时链接再改名），不支持时复制。
+    /// 临时文件名保留完整文件名，只差扩展名的两个文件不会互相覆盖
+    async fn link_or_copy(existing: &Path, target: &Path) -> Result<(), std::io::Error> {
+        let mut temp_name = target.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".part-link");
+        let temp = target.with_file_name(temp_name);
+        let _ = fs::remove_file(&temp).await;
+        if fs::hard_link(existing, &temp).await.is_err() {
+            fs::copy(existing, &temp).await?;
         }
-        Ok(())
+        fs::rename(&temp, target).await
     }
 
+    fn count_downloaded(info: &SharedInfo) {
+        info.update(|info| {
+            if let Some(count) = info.downloaded_sources {
+                info.downloaded_sources = Some(count + 1);
+            }
+        });
+    }
+
+    /// 输出每个文件的结果并写入 manifest
+    pub(crate) async fn finish_downloads(&self, results: Vec<(Source, Result<Downloaded, String>)>) {
+        // 重试时只传入失败的文件，结束状态仍按整个专辑判断
+        let total = results.len().max(self.sources.len());
+        let mut entries = Vec::with_capacity(results.len());
+        let mut failed = Vec::new();
 
+        for (source, result) in results {
+            if let Err(e) = &result {
+                failed.push(FailedSource { source: source.clone(), error: e.clone() });
+            }
+            match &result {
+                Ok(Downloaded { reused_from: Some(_), .. }) => log_t!("log.file_reused", title = source.title),
+                Ok(_) => log_t!("log.file_downloaded", title = source.title),
+                Err(e) if source.resolution == Resolution::ThumbnailGuess =>
+                    log_t!("log.file_failed_guessed", title = source.title, error = e),
+                Err(e) => log_t!("log.file_failed", title = source.title, error = e),
+            }
+            // 跳过时文件仍是历史中的那一份，不在专辑目录中
+            let skipped_path = result
+                .as_ref()
+                .ok()
+                .filter(|downloaded| downloaded.reused_from.as_ref() == Some(&downloaded.path))
+                .map(|downloaded| downloaded.path.clone());
+            entries.push(ManifestEntry {
+                file_name: Self::sanitize_filename(&source.title),
+                title: source.title,
+                url: source.url,
+                page_url: source.page_url,
+                status: match &result {
+                    Ok(_) if skipped_path.is_some() => EntryStatus::Skipped,
+                    Ok(_) => EntryStatus::Downloaded,
+                    Err(_) => EntryStatus::Failed,
+                },
+                size: result.as_ref().ok().map(|downloaded| downloaded.size),
+                sha256: result.as_ref().ok().map(|downloaded| downloaded.sha256.clone()),
+                error: result.err(),
+                path: skipped_path,
+            });
+        }
+
+        // 保留以前运行时记录、这次没有涉及的文件
+        let download_dir = self.get_download_dir();
+        if let Ok(previous) = Manifest::load(Path::new(&download_dir)).await {
+            let mut kept: Vec<ManifestEntry> = previous.entries
+                .into_iter()
+                .filter(|old| !entries.iter().any(|new| new.file_name == old.file_name))
+                .collect();
+            kept.append(&mut entries);
+            entries = kept;
+        }
+        let mut manifest = Manifest::new(&self.album_url, &self.title, entries);
+        if !self.filter.is_empty() {
+            manifest.filter = Some(self.filter.clone());
+            manifest.filtered_out = self.filtered_out;
+        }
+        if let Err(e) = manifest.save(Path::new(&download_dir)).await {
+            log_t!("log.manifest_failed", error = e);
+        }
+
+        let stopped = self.control.is_stopped();
+        let state = BunkrSpiderState::after_downloads(total, failed.len(), stopped, self.analyzed_all());
+        let pages_failed = self.info.get().pages_failed;
+        if self.loaded && !stopped && pages_failed > 0 {
+            log_t!("log.pages_unresolved", count = pages_failed);
+        }
+        match state {
+            BunkrSpiderState::Finished => log_t!("log.all_downloaded", total = total),
+            BunkrSpiderState::Failed if !self.loaded => log_t!("log.album_not_loaded", url = self.album_url),
+            BunkrSpiderState::Failed => log_t!("log.all_failed", total = total),
+            BunkrSpiderState::Cancelled => log_t!("log.stopped_summary", downloaded = total - failed.len(), total = total),
+            _ => log_t!("log.failed_summary", failed = failed.len(), total = total),
+        }
+        self.info.update(|info| {
+            info.state = state;
+            info.failed = failed;
+        });
+    }
+
+
+    #[allow(clippy::too_many_arguments)]
     async fn download_with_retry(
         client: &reqwest::Client,
+        mirrors: &MirrorConfig,
         download_dir: String,
-        url: String,
-        filename: &str,
+        source: &Source,
         index: usize,
-        should_stop: Arc<AtomicBool>,
-        info: Arc<tokio::sync::RwLock<BunkrSpiderInformation>>,
-    ) -> Result<(), String> {
+        retries: usize,
+        control: SpiderControl,
+        info: SharedInfo,
+    ) -> Result<Downloaded, String> {
+        let filename = source.title.as_str();
         let mut last_error = None;
         
-        for attempt in 0..=RETRY_COUNT {
-            if should_stop.load(Ordering::Relaxed) {
+        for attempt in 0..=retries {
+            control.wait_while_paused().await;
+            if control.is_stopped() {
                 return Err("Task stopped".to_string());
             }
 
             if attempt > 0 {
-                egui_println!("try {} times for: {}", attempt, filename);
+                log_t!("log.retry_attempt", attempt = attempt, title = filename);
                 tokio::time::sleep(Duration::from_secs(2u64.pow(attempt as u32))).await;
             }
 
-            match Self::download_with_speed_check(
-                client, &download_dir, &url, 
-                filename, index, 
-                Arc::clone(&info)).await {
-                Ok(_) => {
-                    if let Ok(mut info_lock) = info.try_write() {
-                        if let Some(count) = info_lock.downloaded_sources {
-                            info_lock.downloaded_sources = Some(count + 1);
-                        }
+            let mut result = Self::download_with_speed_check(
+                client, &download_dir, source, &source.url,
+                index, &control,
+                info.clone()).await;
+
+            // DNS/连接失败时先换其他 CDN 主机名再算作一次失败
+            if let Err(e) = &result && mirrors::is_connection_error(e.as_ref()) {
+                for alternative in mirrors::cdn_alternatives(&source.url, mirrors) {
+                    if control.is_stopped() {
+                        break;
+                    }
+                    log_t!("log.cdn_unreachable", title = filename, url = alternative);
+                    result = Self::download_with_speed_check(
+                        client, &download_dir, source, &alternative,
+                        index, &control,
+                        info.clone()).await;
+                    match &result {
+                        Err(e) if mirrors::is_connection_error(e.as_ref()) => continue,
+                        _ => break,
                     }
-                    return Ok(());
+                }
+            }
+
+            match result {
+                Ok(downloaded) => {
+                    Self::count_downloaded(&info);
+                    return Ok(downloaded);
                 },
                 Err(e) => {
                     let error_msg = e.to_string();
-                    egui_println!("download try {} failed: {} - {}", attempt + 1, filename, error_msg);
+                    log_t!("log.attempt_failed", attempt = attempt + 1, title = filename, error = error_msg);
                     last_error = Some(error_msg);
                 }
             }
@@ -310,29 +1217,69 @@ impl BunkrSpider{
     async fn download_with_speed_check(
         client: &reqwest::Client,
         download_dir: &str,
+        source: &Source,
         url: &str,
-        filename: &str,
         index: usize,
-        _info: Arc<tokio::sync::RwLock<BunkrSpiderInformation>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        control: &SpiderControl,
+        _info: SharedInfo,
+    ) -> Result<Downloaded, Box<dyn std::error::Error + Send + Sync>> {
         let start_time = Instant::now();
+        let filename = source.title.as_str();
         
-        egui_println!("start download [{}/?]: {}", index + 1, filename);
+        log_t!("log.download_started", index = index + 1, title = filename);
         
-        let response = client.get(url).send().await?;
+        let response = http::with_referer(client.get(url), &source.page_url)
+            .send()
+            .await?
+            .error_for_status()?;
         let total_size = response.content_length().unwrap_or(0);
         
         // 确保文件名有效
-        let safe_filename = Self::sanitize_filename(filename);
+        let safe_filename = source.file_name();
         let filepath = format!("{}/{}", download_dir, safe_filename);
 
         let mut file = fs::File::create(&filepath).await?;
+        let written = Self::write_body(response, &mut file, control, start_time, total_size).await;
+        drop(file);
+        let (downloaded, sha256, avg_speed_bps) = match written {
+            Ok(written) => written,
+            Err(e) => {
+                // 不留下写了一半的文件，免得下次同步时当作已下载
+                let _ = fs::remove_file(&filepath).await;
+                return Err(e);
+            }
+        };
+
+        log_t!("log.download_finished", index = index + 1, title = filename,
+               speed = avg_speed_bps, size = format!("{:.3}", downloaded as f64 / 1000.0));
+        Ok(Downloaded {
+            size: downloaded,
+            sha256,
+            path: PathBuf::from(filepath),
+            reused_from: None,
+        })
+    }
+
+    /// 把响应内容写入文件，返回写入的字节数、SHA-256 和平均速度（B/s）
+    async fn write_body(
+        response: reqwest::Response,
+        file: &mut fs::File,
+        control: &SpiderControl,
+        start_time: Instant,
+        total_size: u64,
+    ) -> Result<(u64, String, u64), Box<dyn std::error::Error + Send + Sync>> {
         let mut stream = response.bytes_stream();
         let mut downloaded: u64 = 0;
+        let mut hasher = Sha256::new();
 
         while let Some(chunk) = stream.next().await {
             let chunk = chunk?;
-            file.write(&chunk).await?;
+            control.wait_while_paused().await;
+            if control.is_stopped() {
+                return Err("Task stopped".into());
+            }
+            file.write_all(&chunk).await?;
+            hasher.update(&chunk);
             downloaded += chunk.len() as u64;
 
             // 更新进度信息
@@ -344,22 +1291,20 @@ impl BunkrSpider{
             let elapsed = start_time.elapsed().as_secs();
             if elapsed >= 3 {
                 let speed_bps = downloaded / elapsed;
-                if speed_bps < MIN_SPEED_BPS {
-                    return Err(format!("download speed too low: {} B/s < {} B/s", speed_bps, MIN_SPEED_BPS).into());
+                if let Some(min_speed) = MIN_SPEED_BPS && speed_bps < min_speed {
+                    return Err(format!("download speed too low: {} B/s < {} B/s", speed_bps, min_speed).into());
                 }
             }
         }
+        file.flush().await?;
 
         let total_elapsed = start_time.elapsed().as_secs().max(1);
         let avg_speed_bps = downloaded / total_elapsed;
-        
-        if avg_speed_bps < MIN_SPEED_BPS {
-            return Err(format!("dpd too low: {} B/s < {} B/s", avg_speed_bps, MIN_SPEED_BPS).into());
-        }
 
-        egui_println!("downloaded [{}]: {} (speed: {} B/s, size: {:.3} kb)", 
-                 index + 1, filename, avg_speed_bps, downloaded as f64 / 1000.0);
-        Ok(())
+        if let Some(min_speed) = MIN_SPEED_BPS && avg_speed_bps < min_speed {
+            return Err(format!("dpd too low: {} B/s < {} B/s", avg_speed_bps, min_speed).into());
+        }
+        Ok((downloaded, format!("{:x}", hasher.finalize()), avg_speed_bps))
     }
 
     fn sanitize_filename(filename: &str) -> String {
@@ -383,7 +1328,7 @@ impl BunkrSpider{
         let mut entries = match fs::read_dir(download_dir).await {
             Ok(entries) => entries,
             Err(e) => {
-                egui_println!("Failed to read directory {}: {}", download_dir.display(), e);
+                log_t!("log.read_dir_failed", path = download_dir.display(), error = e);
                 return;
             }
         };
@@ -392,63 +1337,150 @@ impl BunkrSpider{
             let entry = match entry {
                 Ok(entry) => entry,
                 Err(e) => {
-                    egui_println!("Failed to read directory entry: {}", e);
+                    log_t!("log.read_entry_failed", error = e);
                     continue;
                 }
             };
             let path = entry.path();
 
-            if !path.is_file() {
+            if !path.is_file() || path.file_name().is_some_and(|name| name == MANIFEST_FILE) {
                 continue;
             }
 
             let metadata = match fs::metadata(&path).await {
                 Ok(metadata) => metadata,
                 Err(e) => {
-                    egui_println!("Failed to get metadata for {}: {}", path.display(), e);
+                    log_t!("log.metadata_failed", path = path.display(), error = e);
                     continue;
                 }
             };
             // delete small files
             if metadata.len() < MIN_FILE_SIZE {
                 if let Err(e) = fs::remove_file(&path).await {
-                    egui_println!("Failed to delete {}: {}", path.display(), e);
+                    log_t!("log.delete_failed", path = path.display(), error = e);
                 } else {
-                    egui_println!("Deleted small file: {}", path.display());
+                    log_t!("log.deleted_small_file", path = path.display());
                 }
             }
         }
     }
 
     pub fn get_state(&self) -> BunkrSpiderState {
-        self.info.try_read()
-            .ok()
-            .map(|info| info.state)
-            .unwrap_or(BunkrSpiderState::Idle)
+        self.info.get().state
     }
 
-    pub fn get_info(&self) -> Option<BunkrSpiderInformation> {
-        self.info.try_read().ok().map(|info| BunkrSpiderInformation {
-            total_sources: info.total_sources,
-            downloaded_sources: info.downloaded_sources,
-            state: info.state,
-        })
+    pub fn get_info(&self) -> BunkrSpiderInformation {
+        self.info.get()
+    }
+
+    /// 与爬虫共享的进度信息，运行期间也可以从其他任务读取
+    pub fn shared_info(&self) -> SharedInfo {
+        self.info.clone()
     }
 
     pub fn stop(&self) {
-        self.should_stop.store(true, Ordering::Relaxed);
-        egui_println!("Stop signal sent, task will terminate gracefully...");
+        self.control.stop();
+        log_t!("log.stop_sent");
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.control.is_stopped()
+    }
+
+    /// 停止和暂停标志，供正在运行时从其他任务控制
+    pub fn control(&self) -> SpiderControl {
+        self.control.clone()
     }
 
     pub fn reset(&mut self) {
-        self.should_stop.store(false, Ordering::Relaxed);
-        if let Ok(mut info) = self.info.try_write() {
-            info.state = BunkrSpiderState::Idle;
-            info.total_sources = None;
-            info.downloaded_sources = None;
-        }
+        self.control.clear();
+        self.info.update(|info| *info = BunkrSpiderInformation::default());
         self.sources.clear();
+        self.filtered_out = 0;
+        self.album_url.clear();
+        self.loaded = false;
+        self.file_pages.clear();
+        self.listing_complete = false;
+        self.no_album_folder = false;
         self.title.clear();
         self.base_dir.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terminal_state_accounts_for_analysis() {
+        use BunkrSpiderState::*;
+        assert_eq!(BunkrSpiderState::after_downloads(3, 0, false, true), Finished);
+        assert_eq!(BunkrSpiderState::after_downloads(0, 0, false, true), Finished);
+        assert_eq!(BunkrSpiderState::after_downloads(3, 1, false, true), PartiallyCompleted);
+        assert_eq!(BunkrSpiderState::after_downloads(3, 3, false, true), Failed);
+        // 专辑没能载入
+        assert_eq!(BunkrSpiderState::after_downloads(0, 0, false, false), Failed);
+        // 有页面没分析出文件，其余都下载成功
+        assert_eq!(BunkrSpiderState::after_downloads(3, 0, false, false), PartiallyCompleted);
+        // 分析时停止，已交给下载的文件都完成了
+        assert_eq!(BunkrSpiderState::after_downloads(2, 0, true, false), Cancelled);
+        assert_eq!(BunkrSpiderState::after_downloads(3, 1, true, true), Cancelled);
+        assert_eq!(BunkrSpiderState::after_downloads(3, 0, true, true), Finished);
+    }
+
+    #[test]
+    fn shared_info_keeps_every_update_under_concurrent_reads() {
+        const WRITERS: usize = 8;
+        const UPDATES: usize = 2000;
+
+        let info = SharedInfo::new();
+        info.update(|info| {
+            info.total_sources = Some(0);
+            info.downloaded_sources = Some(0);
+        });
+        let done = Arc::new(AtomicBool::new(false));
+
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let info = info.clone();
+                let done = Arc::clone(&done);
+                std::thread::spawn(move || {
+                    let mut last = 0;
+                    while !done.load(Ordering::Relaxed) {
+                        let count = info.get().downloaded_sources.unwrap_or(0);
+                        assert!(count >= last, "counter went backwards: {} < {}", count, last);
+                        last = count;
+                    }
+                })
+            })
+            .collect();
+
+        let writers: Vec<_> = (0..WRITERS)
+            .map(|_| {
+                let info = info.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..UPDATES {
+                        BunkrSpider::count_downloaded(&info);
+                        BunkrSpider::add_page_progress(&info, 1, 1);
+                    }
+                    info.update(|info| info.state = BunkrSpiderState::Downloading);
+                })
+            })
+            .collect();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        info.update(|info| info.state = BunkrSpiderState::Finished);
+        done.store(true, Ordering::Relaxed);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+
+        let result = info.get();
+        assert_eq!(result.downloaded_sources, Some(WRITERS * UPDATES));
+        assert_eq!(result.pages_discovered, WRITERS * UPDATES);
+        assert_eq!(result.pages_parsed, WRITERS * UPDATES);
+        assert_eq!(result.state, BunkrSpiderState::Finished);
+    }
+}
@@ -0,0 +1,489 @@
+use rust_spider::bunkr::{self, BunkrSpider, Source};
+use rust_spider::client::BunkrClient;
+use rust_spider::config::{self, Config, FilterConfig, MirrorsConfig, NetworkConfig, Size};
+use rust_spider::export::ExportFormat;
+use rust_spider::history::{DuplicateAction, History};
+use rust_spider::{i18n, t};
+use rust_spider::logger;
+use rust_spider::media::{self, MediaKind};
+use rust_spider::verify;
+use rust_spider::watch::{self, WatchList};
+
+use clap::{Parser, Subcommand};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 命令行模式：带参数启动时使用，不打开窗口
+#[derive(Parser, Debug)]
+#[command(name = "rust_spider", version, about = "Download files from bunkr albums",
+          subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Album (/a/) or file (/f/, /v/, /i/) url
+    #[arg(required = true)]
+    pub url: Option<String>,
+
+    /// Extra config file, applied over the user and project config files
+    #[arg(long, value_name = "FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Directory the album folder is created in [default: .]
+    #[arg(short, long)]
+    pub dir: Option<String>,
+
+    /// List the discovered files with their sizes and exit
+    #[arg(long)]
+    pub list: bool,
+
+    /// Show the discovered files and ask which ones to download
+    #[arg(short, long, conflicts_with = "list")]
+    pub interactive: bool,
+
+    /// Download only the given items of the list, e.g. "1-5,8"
+    #[arg(long, value_name = "RANGES", conflicts_with_all = ["list", "interactive"])]
+    pub select: Option<String>,
+
+    /// Start downloading while the album is still being analyzed
+    #[arg(long, conflicts_with_all = ["list", "interactive", "select"])]
+    pub pipelined: bool,
+
+    /// Dry run: analyze only and print the links to stdout
+    /// (urls, aria2, curl, wget, json, csv)
+    #[arg(long, value_name = "FORMAT", value_parser = parse_export_format,
+          conflicts_with_all = ["list", "interactive", "select", "pipelined"])]
+    pub export: Option<ExportFormat>,
+
+    /// Delete files smaller than 200 B after downloading
+    #[arg(long, overrides_with = "no_delete_invalid")]
+    pub delete_invalid: bool,
+
+    /// Keep small files, even if a config file enables delete_invalid
+    #[arg(long, overrides_with = "delete_invalid")]
+    pub no_delete_invalid: bool,
+
+    /// What to do with files found in the download history
+    /// (download, skip, hardlink) [default: hardlink]
+    #[arg(long, value_name = "ACTION", value_parser = parse_duplicate_action)]
+    pub duplicates: Option<DuplicateAction>,
+
+    /// Neither read nor write the download history
+    #[arg(long, overrides_with = "history")]
+    pub no_history: bool,
+
+    /// Use the download history, even if a config file disables it
+    #[arg(long, overrides_with = "no_history")]
+    pub history: bool,
+
+    /// Put a single file link into a folder named after the file
+    #[arg(long, overrides_with = "no_single_file_folder")]
+    pub single_file_folder: bool,
+
+    /// Download a single file link into the directory itself,
+    /// even if a config file enables single_file_folder
+    #[arg(long, overrides_with = "single_file_folder")]
+    pub no_single_file_folder: bool,
+
+    /// Netscape cookies.txt to send with every request
+    #[arg(long, value_name = "FILE")]
+    pub cookies: Option<String>,
+
+    /// Proxy for every request, e.g. http://127.0.0.1:8080
+    #[arg(long, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Extra request header, "Name: value" (repeatable)
+    #[arg(short = 'H', long = "header", value_name = "HEADER")]
+    pub headers: Vec<String>,
+
+    /// Mirror domains to try, comma separated
+    #[arg(long, value_name = "DOMAINS")]
+    pub mirrors: Option<String>,
+
+    /// Maximum number of pages requested per album [default: 2000]
+    #[arg(long)]
+    pub max_pages: Option<usize>,
+
+    /// Number of file pages requested at the same time [default: 8]
+    #[arg(long)]
+    pub page_concurrency: Option<usize>,
+
+    /// Number of files downloaded at the same time [default: 8]
+    #[arg(long)]
+    pub download_concurrency: Option<usize>,
+
+    /// Times a failed file is retried [default: 3]
+    #[arg(long)]
+    pub retries: Option<usize>,
+
+    /// Only keep these media kinds, comma separated
+    /// (image, video, audio, archive, document, other)
+    #[arg(long, value_name = "KINDS")]
+    pub only: Option<String>,
+
+    /// Drop these media kinds, comma separated
+    #[arg(long, value_name = "KINDS")]
+    pub exclude_kind: Option<String>,
+
+    /// Only keep these extensions, comma separated (e.g. "mp4,mkv")
+    #[arg(long, value_name = "EXTS")]
+    pub ext: Option<String>,
+
+    /// Drop these extensions, comma separated
+    #[arg(long, value_name = "EXTS")]
+    pub exclude_ext: Option<String>,
+
+    /// Drop files smaller than this (e.g. 500K, 2M)
+    #[arg(long, value_name = "SIZE")]
+    pub min_size: Option<String>,
+
+    /// Drop files larger than this (e.g. 500K, 2M)
+    #[arg(long, value_name = "SIZE")]
+    pub max_size: Option<String>,
+
+    /// Only keep files whose name matches this regex
+    #[arg(long, value_name = "REGEX")]
+    pub name: Option<String>,
+
+    /// Drop files whose name matches this regex
+    #[arg(long, value_name = "REGEX")]
+    pub exclude_name: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Re-hash an album folder against its manifest.json and report
+    /// missing, truncated, modified and extra files
+    Verify {
+        /// Album folder containing manifest.json
+        dir: String,
+    },
+    /// Download again only the files marked as failed in an album folder's manifest.json
+    Retry {
+        /// Album folder containing manifest.json
+        dir: String,
+        /// Files downloaded at the same time
+        #[arg(long, default_value_t = 2)]
+        concurrency: usize,
+        /// Download from another CDN host instead of the recorded one
+        #[arg(long)]
+        switch_cdn: bool,
+    },
+    /// Manage albums that are re-checked for new uploads
+    Watch {
+        #[command(subcommand)]
+        action: WatchAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WatchAction {
+    /// Start watching an album
+    Add {
+        url: String,
+        /// Directory the album folder is created in
+        #[arg(short, long, default_value = ".")]
+        dir: String,
+        /// Minutes between checks
+        #[arg(long, default_value_t = watch::DEFAULT_INTERVAL_MINUTES)]
+        interval: u64,
+    },
+    /// Stop watching an album
+    Remove { url: String },
+    /// Show the watched albums
+    List,
+    /// Keep checking the watched albums and download new files
+    Run {
+        /// Check every album once and exit
+        #[arg(long)]
+        once: bool,
+    },
+}
+
+impl Cli {
+    /// 命令行上给出的选项，作为优先级最高的一层配置
+    fn overrides(&self) -> Result<Config, String> {
+        // 成对的 --x / --no-x 以后出现的为准，都没给出时沿用配置文件
+        let flag = |enabled: bool, disabled: bool| enabled.then_some(true).or(disabled.then_some(false));
+        let list = |text: &Option<String>| text.as_deref().map(config::split_list);
+        Ok(Config {
+            dir: self.dir.clone(),
+            delete_invalid: flag(self.delete_invalid, self.no_delete_invalid),
+            single_file_folder: flag(self.single_file_folder, self.no_single_file_folder),
+            duplicates: self.duplicates,
+            history: flag(self.history, self.no_history),
+            language: None,
+            network: NetworkConfig {
+                headers: config::header_map(&self.headers)?,
+                cookies: self.cookies.as_ref().map(Into::into),
+                proxy: self.proxy.clone(),
+                retries: self.retries,
+                max_pages: self.max_pages,
+                page_concurrency: self.page_concurrency,
+                download_concurrency: self.download_concurrency,
+                ..Default::default()
+            },
+            mirrors: MirrorsConfig {
+                domains: list(&self.mirrors),
+                ..Default::default()
+            },
+            filter: FilterConfig {
+                only: list(&self.only),
+                exclude_kinds: list(&self.exclude_kind),
+                extensions: list(&self.ext),
+                exclude_extensions: list(&self.exclude_ext),
+                min_size: self.min_size.clone().map(Size::Text),
+                max_size: self.max_size.clone().map(Size::Text),
+                name: self.name.clone(),
+                exclude_name: self.exclude_name.clone(),
+            },
+        })
+    }
+
+    /// 配置文件、环境变量和命令行选项叠加后的配置
+    fn config(&self) -> Result<Config, String> {
+        let overrides = self.overrides()?;
+        overrides.check("command line options")?;
+        Ok(Config::load(self.config.as_deref())?.merge(overrides))
+    }
+}
+
+fn client(config: &Config) -> Result<BunkrClient, Box<dyn std::error::Error>> {
+    let mut builder = BunkrClient::builder().config(config.client_config()?);
+    if config.use_history() {
+        let history = Arc::new(tokio::sync::Mutex::new(History::open_default()));
+        builder = builder.history(history, config.duplicate_action());
+    }
+    builder.build().map_err(|e| e as Box<dyn std::error::Error>)
+}
+
+pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    logger::get_logger()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .set_echo_stdout(cli.export.is_none());
+
+    if let Some(Command::Verify { dir }) = &cli.command {
+        let dir = Path::new(dir);
+        let report = verify::verify_dir(dir).await?;
+        report.print(dir);
+        if !report.is_clean() {
+            return Err("verification found problems".into());
+        }
+        return Ok(());
+    }
+    let config = cli.config()?;
+    if let Some(language) = config.language {
+        i18n::set_language(language);
+    }
+    if let Some(Command::Watch { action }) = cli.command {
+        return run_watch(action, &config).await;
+    }
+    let mut spider = client(&config)?.spider();
+
+    if let Some(Command::Retry { dir, concurrency, switch_cdn }) = &cli.command {
+        let count = spider.load_failed(Path::new(dir)).await?;
+        if count == 0 {
+            println!("{}", t!("cli.no_failed_files", dir = dir));
+            return Ok(());
+        }
+        let options = bunkr::RetryOptions {
+            concurrency: Some(*concurrency),
+            switch_cdn: *switch_cdn,
+        };
+        spider.retry_failed(options).await?;
+        return download_result(&spider);
+    }
+
+    let url = cli.url.clone().unwrap_or_default();
+    if cli.pipelined {
+        spider.run_pipelined(config.download_dir(), url).await?;
+    } else {
+        spider.run(config.download_dir(), url).await;
+
+        if let Some(format) = cli.export {
+            // 空的导出结果会被当作空专辑，必须以错误退出
+            if !spider.is_loaded() {
+                return Err(t!("cli.could_not_load", url = spider.album_url()).into());
+            }
+            if format.needs_size() {
+                spider.probe_sizes().await;
+            }
+            print!("{}", spider.export(format));
+            return Ok(());
+        }
+
+        if cli.list || cli.interactive || cli.select.is_some() {
+            if !spider.is_loaded() {
+                return Err(t!("cli.could_not_load", url = spider.album_url()).into());
+            }
+            spider.probe_sizes().await;
+            print_sources(spider.sources());
+            if cli.list {
+                return Ok(());
+            }
+
+            let count = spider.sources().len();
+            let selected = match &cli.select {
+                Some(ranges) => parse_selection(ranges, count)?,
+                None => prompt_selection(count)?,
+            };
+            spider.retain_sources(&selected);
+        }
+
+        if !spider.is_loaded() {
+            spider.finish_not_loaded();
+            return download_result(&spider);
+        }
+        spider.download_all().await?;
+    }
+
+    if config.delete_invalid.unwrap_or(false) {
+        spider.clean_error_files().await;
+    }
+
+    download_result(&spider)
+}
+
+/// 有文件没下载成功时返回错误，以非零状态退出方便脚本判断
+fn download_result(spider: &BunkrSpider) -> Result<(), Box<dyn std::error::Error>> {
+    if !spider.is_loaded() {
+        return Err(t!("cli.could_not_load", url = spider.album_url()).into());
+    }
+    let info = spider.get_info();
+    match info.state {
+        bunkr::BunkrSpiderState::Failed
+        | bunkr::BunkrSpiderState::PartiallyCompleted
+        | bunkr::BunkrSpiderState::Cancelled => {
+            Err(t!("cli.not_downloaded", failed = info.failed.len(), total = spider.sources().len()).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn run_watch(action: WatchAction, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let mut list = WatchList::load();
+    match action {
+        WatchAction::Add { url, dir, interval } => {
+            if list.add(&url, &dir, interval) {
+                println!("{}", t!("cli.watching", url = url, interval = interval));
+            } else {
+                println!("{}", t!("cli.watch_updated", url = url));
+            }
+            list.save()?;
+        }
+        WatchAction::Remove { url } => {
+            if !list.remove(&url) {
+                return Err(t!("cli.not_watched", url = url).into());
+            }
+            list.save()?;
+        }
+        WatchAction::List => {
+            for album in &list.albums {
+                let line = match &album.last_error {
+                    Some(error) => t!(
+                        "cli.watch_entry_error",
+                        url = album.url,
+                        interval = album.interval_minutes,
+                        dir = album.base_dir,
+                        added = album.last_added,
+                        removed = album.last_removed,
+                        error = error
+                    ),
+                    None => t!(
+                        "cli.watch_entry",
+                        url = album.url,
+                        interval = album.interval_minutes,
+                        dir = album.base_dir,
+                        added = album.last_added,
+                        removed = album.last_removed
+                    ),
+                };
+                println!("{}", line);
+            }
+        }
+        WatchAction::Run { once } => {
+            let list = std::sync::Mutex::new(list);
+            let mut spider = client(config)?.spider();
+            loop {
+                watch::sync_due(&list, &mut spider, once).await;
+                if once {
+                    break;
+                }
+                tokio::time::sleep(watch::TICK).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_duplicate_action(text: &str) -> Result<DuplicateAction, String> {
+    DuplicateAction::parse(text).ok_or_else(|| format!("unknown duplicate action: {}", text))
+}
+
+fn parse_export_format(text: &str) -> Result<ExportFormat, String> {
+    ExportFormat::parse(text).ok_or_else(|| format!("unknown export format: {}", text))
+}
+
+fn print_sources(sources: &[Source]) {
+    for (index, source) in sources.iter().enumerate() {
+        println!(
+            "{:>4}  {:<8}  {:<5}  {:>10}  {}",
+            index + 1,
+            MediaKind::from_file_name(&source.title).label(),
+            media::extension_of(&source.title).to_lowercase(),
+            source.size.map(media::format_size).unwrap_or_else(|| "?".to_string()),
+            source.title,
+        );
+    }
+}
+
+fn prompt_selection(count: usize) -> Result<Vec<bool>, Box<dyn std::error::Error>> {
+    let stdin = io::stdin();
+    loop {
+        print!("{}", t!("cli.select_prompt"));
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(vec![false; count]);
+        }
+        match parse_selection(&line, count) {
+            Ok(selected) => return Ok(selected),
+            Err(e) => println!("{}", e),
+        }
+    }
+}
+
+/// 解析 "1-5,8"、"all"、"none" 形式的选择，序号从 1 开始
+fn parse_selection(text: &str, count: usize) -> Result<Vec<bool>, String> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("all") {
+        return Ok(vec![true; count]);
+    }
+    if text.is_empty() || text.eq_ignore_ascii_case("none") {
+        return Ok(vec![false; count]);
+    }
+
+    let mut selected = vec![false; count];
+    for part in text.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (part, part),
+        };
+        let parse = |n: &str| {
+            n.parse::<usize>()
+                .ok()
+                .filter(|n| (1..=count).contains(n))
+                .ok_or_else(|| format!("invalid item number: {} (1-{})", n, count))
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err(format!("invalid range: {}", part));
+        }
+        selected[start - 1..end].iter_mut().for_each(|s| *s = true);
+    }
+    Ok(selected)
+}
@@ -0,0 +1,246 @@
+use crate::bunkr::{BunkrSpider, Downloaded, Source};
+use crate::filter::SourceFilter;
+use crate::history::{DuplicateAction, History};
+use crate::http::{self, HttpConfig};
+use crate::mirrors::MirrorConfig;
+
+use futures::future;
+use futures::stream::{self, BoxStream, StreamExt};
+use std::sync::{Arc, Mutex};
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// 客户端的全部设置，默认值与界面和命令行的默认值一致
+#[derive(Clone, Debug)]
+pub struct ClientConfig {
+    pub http: HttpConfig,
+    pub mirrors: MirrorConfig,
+    pub filter: SourceFilter,
+    /// 分析一个专辑时最多请求的页面数
+    pub max_pages: usize,
+    /// 同时请求的文件页数
+    pub page_concurrency: usize,
+    /// 同时下载的文件数
+    pub download_concurrency: usize,
+    /// 单个文件失败后的重试次数
+    pub retries: usize,
+    /// 单文件链接是否下载到以文件名命名的文件夹
+    pub single_file_folder: bool,
+    pub duplicate_action: DuplicateAction,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            http: HttpConfig::default(),
+            mirrors: MirrorConfig::default(),
+            filter: SourceFilter::default(),
+            max_pages: crate::bunkr::DEFAULT_MAX_PAGES,
+            page_concurrency: crate::bunkr::DEFAULT_ANALYZE_CONCURRENT_LIMIT,
+            download_concurrency: crate::bunkr::DEFAULT_DOWNLOAD_CONCURRENT_LIMIT,
+            retries: crate::bunkr::DEFAULT_RETRY_COUNT,
+            single_file_folder: false,
+            duplicate_action: DuplicateAction::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct BunkrClientBuilder {
+    config: ClientConfig,
+    history: Option<Arc<tokio::sync::Mutex<History>>>,
+}
+
+impl BunkrClientBuilder {
+    /// 整体替换设置
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn http(mut self, http: HttpConfig) -> Self {
+        self.config.http = http;
+        self
+    }
+
+    pub fn mirrors(mut self, mirrors: MirrorConfig) -> Self {
+        self.config.mirrors = mirrors;
+        self
+    }
+
+    pub fn filter(mut self, filter: SourceFilter) -> Self {
+        self.config.filter = filter;
+        self
+    }
+
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.config.max_pages = max_pages;
+        self
+    }
+
+    pub fn page_concurrency(mut self, concurrency: usize) -> Self {
+        self.config.page_concurrency = concurrency;
+        self
+    }
+
+    pub fn download_concurrency(mut self, concurrency: usize) -> Self {
+        self.config.download_concurrency = concurrency;
+        self
+    }
+
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.config.retries = retries;
+        self
+    }
+
+    pub fn single_file_folder(mut self, enabled: bool) -> Self {
+        self.config.single_file_folder = enabled;
+        self
+    }
+
+    /// 使用下载历史查重并记录，不设置时不读写历史
+    pub fn history(mut self, history: Arc<tokio::sync::Mutex<History>>, action: DuplicateAction) -> Self {
+        self.history = Some(history);
+        self.config.duplicate_action = action;
+        self
+    }
+
+    /// 建立 HTTP 客户端并检查筛选条件，设置有误时返回错误
+    pub fn build(self) -> Result<BunkrClient, Error> {
+        let http_client = http::build_client(&self.config.http).map_err(|e| e.to_string())?;
+        self.config.filter.compile()?;
+        Ok(BunkrClient {
+            config: self.config,
+            history: self.history,
+            http_client,
+        })
+    }
+}
+
+/// 分析好的专辑：规范化后的链接、标题和筛选后的文件
+#[derive(Clone, Debug)]
+pub struct Album {
+    pub url: String,
+    pub title: String,
+    pub sources: Vec<Source>,
+    /// 被筛选条件排除的文件数
+    pub filtered_out: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct DownloadOptions {
+    /// 专辑文件夹所在的目录
+    pub base_dir: String,
+    /// 下载结束后删除过小的无效文件
+    pub delete_invalid: bool,
+}
+
+impl DownloadOptions {
+    pub fn new(base_dir: impl Into<String>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+            delete_invalid: false,
+        }
+    }
+}
+
+/// `download` 产出的进度事件
+#[derive(Clone, Debug)]
+pub enum Event {
+    Started { total: usize },
+    FileFinished { source: Source, result: Result<Downloaded, String> },
+    /// manifest 已写入，所有文件都处理完
+    Finished { downloaded: usize, failed: usize },
+}
+
+/// 库的入口：`BunkrClient::builder().config(..).build()`
+#[derive(Clone)]
+pub struct BunkrClient {
+    config: ClientConfig,
+    history: Option<Arc<tokio::sync::Mutex<History>>>,
+    http_client: reqwest::Client,
+}
+
+impl BunkrClient {
+    pub fn builder() -> BunkrClientBuilder {
+        BunkrClientBuilder::default()
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// 按设置新建一个爬虫，界面和命令行也通过它共享同一套配置
+    pub fn spider(&self) -> BunkrSpider {
+        let mut spider = BunkrSpider::new();
+        self.configure(&mut spider);
+        spider
+    }
+
+    /// 把设置应用到已有的爬虫上，保留它的状态和控制标志
+    pub fn configure(&self, spider: &mut BunkrSpider) {
+        spider.set_client(self.http_client.clone());
+        spider.set_mirrors(self.config.mirrors.clone());
+        spider.set_single_file_folder(self.config.single_file_folder);
+        spider.set_max_pages(self.config.max_pages);
+        spider.set_analyze_concurrency(self.config.page_concurrency);
+        spider.set_download_concurrency(self.config.download_concurrency);
+        spider.set_retries(self.config.retries);
+        // 筛选条件已在 build 时检查过
+        let _ = spider.set_filter(self.config.filter.clone());
+        spider.set_history(self.history.clone());
+        spider.set_duplicate_action(self.config.duplicate_action);
+    }
+
+    /// 分析专辑（或单个文件链接），得到筛选后的文件列表；专辑页无法载入时返回错误
+    pub async fn resolve_album(&self, url: &str) -> Result<Album, Error> {
+        let mut spider = self.spider();
+        spider.run(String::new(), url.to_string()).await;
+
+        if !spider.is_loaded() {
+            return Err(format!("could not load {}", url).into());
+        }
+        Ok(Album {
+            url: spider.album_url().to_string(),
+            title: spider.title().to_string(),
+            sources: spider.sources().to_vec(),
+            filtered_out: spider.filtered_out(),
+        })
+    }
+
+    /// 下载专辑中的文件，每个文件完成时产出一个事件，最后写入 manifest
+    pub fn download(&self, album: Album, options: DownloadOptions) -> BoxStream<'static, Event> {
+        let mut spider = self.spider();
+        let total = album.sources.len();
+        let sources = album.sources.clone();
+        spider.load_album(album.url, album.title, album.sources, options.base_dir);
+
+        let results = Arc::new(Mutex::new(Vec::with_capacity(total)));
+        let collected = results.clone();
+        let files = spider
+            .download_stream(stream::iter(sources))
+            .map(move |(source, result)| {
+                collected
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push((source.clone(), result.clone()));
+                Event::FileFinished { source, result }
+            });
+
+        let finished = stream::once(async move {
+            let results = std::mem::take(&mut *results.lock().unwrap_or_else(|e| e.into_inner()));
+            let downloaded = results.iter().filter(|(_, result)| result.is_ok()).count();
+            let failed = results.len() - downloaded;
+            spider.finish_downloads(results).await;
+            if options.delete_invalid {
+                spider.clean_error_files().await;
+            }
+            Event::Finished { downloaded, failed }
+        });
+
+        stream::once(future::ready(Event::Started { total }))
+            .chain(files)
+            .chain(finished)
+            .boxed()
+    }
+}
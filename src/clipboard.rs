@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// 剪贴板轮询间隔
+pub const POLL_INTERVAL: Duration = Duration::from_millis(800);
+
+/// 在后台线程轮询剪贴板，内容变化时把新文本交给界面。
+/// 开启时剪贴板里已有的内容不算新复制的，drop 后线程自行退出。
+pub struct ClipboardWatcher {
+    receiver: Receiver<String>,
+    running: Arc<AtomicBool>,
+}
+
+impl ClipboardWatcher {
+    pub fn start() -> Result<ClipboardWatcher, arboard::Error> {
+        let mut clipboard = arboard::Clipboard::new()?;
+        let (sender, receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_running = running.clone();
+        std::thread::spawn(move || {
+            let mut last = clipboard.get_text().unwrap_or_default();
+            while thread_running.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL_INTERVAL);
+                let Ok(text) = clipboard.get_text() else {
+                    continue;
+                };
+                if text != last {
+                    last = text.clone();
+                    if sender.send(text).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(ClipboardWatcher { receiver, running })
+    }
+
+    /// 取出自上次调用以来复制的所有文本
+    pub fn take_copied(&self) -> Vec<String> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl Drop for ClipboardWatcher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
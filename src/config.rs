@@ -0,0 +1,571 @@
+use crate::client::ClientConfig;
+use crate::filter::SourceFilter;
+use crate::history::{self, DuplicateAction};
+use crate::http::HttpConfig;
+use crate::i18n::Language;
+use crate::media::MediaKind;
+use crate::mirrors::MirrorConfig;
+
+use regex::Regex;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 用户配置文件名，位于平台配置目录下的 bunkr-spider 中
+pub const CONFIG_FILE: &str = "config.toml";
+/// 项目配置文件名，从当前目录读取
+pub const PROJECT_CONFIG_FILE: &str = "bunkr-spider.toml";
+/// 额外指定一个配置文件的环境变量，优先级高于用户和项目配置
+pub const CONFIG_ENV: &str = "BUNKR_SPIDER_CONFIG";
+/// 覆盖单个选项的环境变量前缀，如 `BUNKR_SPIDER_RETRIES=5`
+pub const ENV_PREFIX: &str = "BUNKR_SPIDER_";
+const MAX_RETRIES: usize = 20;
+
+/// 配置文件的内容。每一层（配置文件、环境变量、命令行）都解析成一个 `Config`，
+/// 未设置的项为 None，按优先级用 `merge` 叠加。
+///
+/// ```toml
+/// dir = "/data/bunkr"
+/// duplicates = "skip"
+/// language = "zh-CN"
+///
+/// [network]
+/// retries = 5
+/// download_concurrency = 4
+/// headers = { Authorization = "Bearer xxx" }
+///
+/// [mirrors]
+/// domains = ["bunkr.cr", "bunkr.si"]
+///
+/// [filter]
+/// only = ["video"]
+/// min_size = "10M"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 专辑文件夹所在的目录
+    pub dir: Option<String>,
+    /// 下载结束后删除过小的无效文件
+    pub delete_invalid: Option<bool>,
+    pub single_file_folder: Option<bool>,
+    pub duplicates: Option<DuplicateAction>,
+    /// 是否读写下载历史
+    pub history: Option<bool>,
+    /// 日志语言，不设置时跟随系统
+    pub language: Option<Language>,
+    pub network: NetworkConfig,
+    pub mirrors: MirrorsConfig,
+    pub filter: FilterConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub user_agent: Option<String>,
+    /// 额外请求头，各层按名称合并
+    pub headers: BTreeMap<String, String>,
+    /// Netscape 格式的 cookies.txt
+    pub cookies: Option<PathBuf>,
+    /// HTTP 代理，如 "http://127.0.0.1:8080"
+    pub proxy: Option<String>,
+    /// 连接超时（秒）
+    pub connect_timeout: Option<u64>,
+    /// 两次读取之间的最长等待时间（秒）
+    pub read_timeout: Option<u64>,
+    /// 单个文件失败后的重试次数
+    pub retries: Option<usize>,
+    pub max_pages: Option<usize>,
+    pub page_concurrency: Option<usize>,
+    pub download_concurrency: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MirrorsConfig {
+    pub domains: Option<Vec<String>>,
+    pub cdn_domains: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    /// 只保留这些媒体类型
+    pub only: Option<Vec<String>>,
+    pub exclude_kinds: Option<Vec<String>>,
+    pub extensions: Option<Vec<String>>,
+    pub exclude_extensions: Option<Vec<String>>,
+    pub min_size: Option<Size>,
+    pub max_size: Option<Size>,
+    /// 文件名需匹配的正则
+    pub name: Option<String>,
+    pub exclude_name: Option<String>,
+}
+
+/// 文件大小，可以写字节数，也可以写 "500K"、"2M" 这样的文本
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Size {
+    Bytes(u64),
+    Text(String),
+}
+
+impl Size {
+    pub fn bytes(&self) -> Result<u64, String> {
+        match self {
+            Size::Bytes(bytes) => Ok(*bytes),
+            Size::Text(text) => SourceFilter::parse_size(text),
+        }
+    }
+}
+
+impl Config {
+    /// 用户配置文件，如 ~/.config/bunkr-spider/config.toml
+    pub fn user_path() -> Option<PathBuf> {
+        history::app_config_dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// 依次叠加用户配置、当前目录的项目配置、`extra`（未给出时看 `BUNKR_SPIDER_CONFIG`）
+    /// 和 `BUNKR_SPIDER_*` 环境变量，后面的覆盖前面的。前两个文件不存在时跳过。
+    pub fn load(extra: Option<&Path>) -> Result<Config, String> {
+        let mut config = Config::default();
+        for path in [Config::user_path(), Some(PathBuf::from(PROJECT_CONFIG_FILE))].into_iter().flatten() {
+            if path.is_file() {
+                config = config.merge(Config::from_file(&path)?);
+            }
+        }
+
+        let extra = extra.map(Path::to_path_buf).or_else(|| std::env::var_os(CONFIG_ENV).map(PathBuf::from));
+        if let Some(path) = extra {
+            config = config.merge(Config::from_file(&path)?);
+        }
+
+        let env = Config::from_env(|name| std::env::var(name).ok())?;
+        env.check("environment")?;
+        Ok(config.merge(env))
+    }
+
+    /// 读取并检查一个配置文件，错误信息带上文件路径。
+    /// 文件中的相对路径（`dir`、`network.cookies`）相对于配置文件所在的目录
+    pub fn from_file(path: &Path) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut config: Config = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if let Some(base) = path.parent() {
+            config.resolve_paths(base);
+        }
+        config.check(&path.display().to_string())?;
+        Ok(config)
+    }
+
+    fn resolve_paths(&mut self, base: &Path) {
+        if let Some(dir) = &mut self.dir
+            && Path::new(dir.as_str()).is_relative()
+        {
+            *dir = base.join(dir.as_str()).to_string_lossy().to_string();
+        }
+        if let Some(cookies) = &mut self.network.cookies
+            && cookies.is_relative()
+        {
+            *cookies = base.join(&*cookies);
+        }
+    }
+
+    /// 从 `BUNKR_SPIDER_*` 环境变量读取，`var` 按完整变量名取值
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Result<Config, String> {
+        let get = |name: &str| {
+            let name = format!("{}{}", ENV_PREFIX, name);
+            var(&name).filter(|value| !value.trim().is_empty()).map(|value| (name, value))
+        };
+        let list = |name: &str| get(name).map(|(_, value)| split_list(&value));
+
+        let duplicates = get("DUPLICATES")
+            .map(|(name, value)| {
+                DuplicateAction::parse(&value).ok_or_else(|| {
+                    let expected: Vec<&str> = DuplicateAction::ALL.iter().map(|a| a.label()).collect();
+                    format!("{}: expected one of {}, got {:?}", name, expected.join(", "), value)
+                })
+            })
+            .transpose()?;
+        let language = get("LANGUAGE")
+            .map(|(name, value)| {
+                Language::from_locale(&value).ok_or_else(|| {
+                    let expected: Vec<&str> = Language::ALL.iter().map(|l| l.code()).collect();
+                    format!("{}: expected one of {}, got {:?}", name, expected.join(", "), value)
+                })
+            })
+            .transpose()?;
+
+        Ok(Config {
+            dir: get("DIR").map(|(_, value)| value),
+            delete_invalid: parse_bool(get("DELETE_INVALID"))?,
+            single_file_folder: parse_bool(get("SINGLE_FILE_FOLDER"))?,
+            duplicates,
+            history: parse_bool(get("HISTORY"))?,
+            language,
+            network: NetworkConfig {
+                user_agent: get("USER_AGENT").map(|(_, value)| value),
+                headers: BTreeMap::new(),
+                cookies: get("COOKIES").map(|(_, value)| value.into()),
+                proxy: get("PROXY").map(|(_, value)| value),
+                connect_timeout: parse_number(get("CONNECT_TIMEOUT"))?,
+                read_timeout: parse_number(get("READ_TIMEOUT"))?,
+                retries: parse_number(get("RETRIES"))?,
+                max_pages: parse_number(get("MAX_PAGES"))?,
+                page_concurrency: parse_number(get("PAGE_CONCURRENCY"))?,
+                download_concurrency: parse_number(get("DOWNLOAD_CONCURRENCY"))?,
+            },
+            mirrors: MirrorsConfig {
+                domains: list("MIRRORS"),
+                cdn_domains: list("CDN_DOMAINS"),
+            },
+            filter: FilterConfig {
+                only: list("ONLY"),
+                exclude_kinds: list("EXCLUDE_KINDS"),
+                extensions: list("EXTENSIONS"),
+                exclude_extensions: list("EXCLUDE_EXTENSIONS"),
+                min_size: get("MIN_SIZE").map(|(_, value)| Size::Text(value)),
+                max_size: get("MAX_SIZE").map(|(_, value)| Size::Text(value)),
+                name: get("NAME").map(|(_, value)| value),
+                exclude_name: get("EXCLUDE_NAME").map(|(_, value)| value),
+            },
+        })
+    }
+
+    /// 用 `other` 中设置了的项覆盖当前的项
+    pub fn merge(self, other: Config) -> Config {
+        let mut headers = self.network.headers;
+        headers.extend(other.network.headers);
+        Config {
+            dir: other.dir.or(self.dir),
+            delete_invalid: other.delete_invalid.or(self.delete_invalid),
+            single_file_folder: other.single_file_folder.or(self.single_file_folder),
+            duplicates: other.duplicates.or(self.duplicates),
+            history: other.history.or(self.history),
+            language: other.language.or(self.language),
+            network: NetworkConfig {
+                user_agent: other.network.user_agent.or(self.network.user_agent),
+                headers,
+                cookies: other.network.cookies.or(self.network.cookies),
+                proxy: other.network.proxy.or(self.network.proxy),
+                connect_timeout: other.network.connect_timeout.or(self.network.connect_timeout),
+                read_timeout: other.network.read_timeout.or(self.network.read_timeout),
+                retries: other.network.retries.or(self.network.retries),
+                max_pages: other.network.max_pages.or(self.network.max_pages),
+                page_concurrency: other.network.page_concurrency.or(self.network.page_concurrency),
+                download_concurrency: other.network.download_concurrency.or(self.network.download_concurrency),
+            },
+            mirrors: MirrorsConfig {
+                domains: other.mirrors.domains.or(self.mirrors.domains),
+                cdn_domains: other.mirrors.cdn_domains.or(self.mirrors.cdn_domains),
+            },
+            filter: FilterConfig {
+                only: other.filter.only.or(self.filter.only),
+                exclude_kinds: other.filter.exclude_kinds.or(self.filter.exclude_kinds),
+                extensions: other.filter.extensions.or(self.filter.extensions),
+                exclude_extensions: other.filter.exclude_extensions.or(self.filter.exclude_extensions),
+                min_size: other.filter.min_size.or(self.filter.min_size),
+                max_size: other.filter.max_size.or(self.filter.max_size),
+                name: other.filter.name.or(self.filter.name),
+                exclude_name: other.filter.exclude_name.or(self.filter.exclude_name),
+            },
+        }
+    }
+
+    /// 检查设置了的项，返回所有问题，每行一个
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let network = &self.network;
+
+        if self.dir.as_deref().is_some_and(|dir| dir.trim().is_empty()) {
+            problems.push("dir: must not be empty".to_string());
+        }
+        if network.user_agent.as_deref().is_some_and(|agent| agent.trim().is_empty()) {
+            problems.push("network.user_agent: must not be empty".to_string());
+        }
+        for (name, value) in &network.headers {
+            if HeaderName::from_str(name).is_err() {
+                problems.push(format!("network.headers: invalid header name {:?}", name));
+            } else if HeaderValue::from_str(value).is_err() {
+                problems.push(format!("network.headers: invalid value for {}", name));
+            }
+        }
+        if let Some(cookies) = &network.cookies
+            && !cookies.is_file()
+        {
+            problems.push(format!("network.cookies: file not found: {}", cookies.display()));
+        }
+        if let Some(proxy) = &network.proxy
+            && let Err(e) = reqwest::Proxy::all(proxy)
+        {
+            problems.push(format!("network.proxy: invalid proxy {:?}: {}", proxy, e));
+        }
+        for (field, value) in [("connect_timeout", network.connect_timeout), ("read_timeout", network.read_timeout)] {
+            if value == Some(0) {
+                problems.push(format!("network.{}: must be at least 1 second", field));
+            }
+        }
+        for (field, value) in [
+            ("max_pages", network.max_pages),
+            ("page_concurrency", network.page_concurrency),
+            ("download_concurrency", network.download_concurrency),
+        ] {
+            if value == Some(0) {
+                problems.push(format!("network.{}: must be at least 1", field));
+            }
+        }
+        if network.retries.is_some_and(|retries| retries > MAX_RETRIES) {
+            problems.push(format!("network.retries: must be at most {}", MAX_RETRIES));
+        }
+
+        for (field, domains) in [("domains", &self.mirrors.domains), ("cdn_domains", &self.mirrors.cdn_domains)] {
+            if domains.as_ref().is_some_and(|domains| domains.iter().all(|d| d.trim().is_empty())) {
+                problems.push(format!("mirrors.{}: must list at least one domain", field));
+            }
+        }
+
+        let filter = &self.filter;
+        for (field, kinds) in [("only", &filter.only), ("exclude_kinds", &filter.exclude_kinds)] {
+            for kind in kinds.iter().flatten() {
+                if MediaKind::parse(kind).is_none() {
+                    let expected: Vec<&str> = MediaKind::ALL.iter().map(|k| k.label()).collect();
+                    problems.push(format!("filter.{}: unknown media kind {:?} (expected {})", field, kind, expected.join(", ")));
+                }
+            }
+        }
+        let mut sizes = [None, None];
+        for (slot, (field, size)) in sizes.iter_mut().zip([("min_size", &filter.min_size), ("max_size", &filter.max_size)]) {
+            match size.as_ref().map(Size::bytes).transpose() {
+                Ok(bytes) => *slot = bytes,
+                Err(e) => problems.push(format!("filter.{}: {} (e.g. 500K, 2M, 1G)", field, e)),
+            }
+        }
+        if let [Some(min), Some(max)] = sizes
+            && min > max
+        {
+            problems.push("filter.min_size: must not be larger than filter.max_size".to_string());
+        }
+        for (field, pattern) in [("name", &filter.name), ("exclude_name", &filter.exclude_name)] {
+            if let Some(pattern) = pattern
+                && let Err(e) = Regex::new(pattern)
+            {
+                problems.push(format!("filter.{}: invalid regex: {}", field, e));
+            }
+        }
+        problems
+    }
+
+    /// 检查配置，有问题时返回带来源说明的错误
+    pub fn check(&self, origin: &str) -> Result<(), String> {
+        let problems = self.validate();
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(format!("invalid configuration in {}:\n  {}", origin, problems.join("\n  ")))
+    }
+
+    pub fn download_dir(&self) -> String {
+        self.dir.clone().unwrap_or_else(|| ".".to_string())
+    }
+
+    pub fn use_history(&self) -> bool {
+        self.history.unwrap_or(true)
+    }
+
+    pub fn duplicate_action(&self) -> DuplicateAction {
+        self.duplicates.unwrap_or_default()
+    }
+
+    /// 把叠加后的配置转成客户端设置，未设置的项使用默认值
+    pub fn client_config(&self) -> Result<ClientConfig, String> {
+        self.check("configuration")?;
+        let defaults = ClientConfig::default();
+        let network = &self.network;
+
+        let mut http = defaults.http;
+        if let Some(user_agent) = &network.user_agent {
+            http.user_agent = user_agent.clone();
+        }
+        http.headers = network.headers.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+        http.cookies_file = network.cookies.clone();
+        http.proxy = network.proxy.clone();
+        if let Some(seconds) = network.connect_timeout {
+            http.connect_timeout = Duration::from_secs(seconds);
+        }
+        if let Some(seconds) = network.read_timeout {
+            http.read_timeout = Duration::from_secs(seconds);
+        }
+
+        let mut mirrors = defaults.mirrors;
+        if let Some(domains) = &self.mirrors.domains {
+            mirrors.domains = MirrorConfig::parse_domains(&domains.join(","));
+        }
+        if let Some(domains) = &self.mirrors.cdn_domains {
+            mirrors.cdn_domains = MirrorConfig::parse_domains(&domains.join(","));
+        }
+
+        Ok(ClientConfig {
+            http,
+            mirrors,
+            filter: self.source_filter()?,
+            max_pages: network.max_pages.unwrap_or(defaults.max_pages),
+            page_concurrency: network.page_concurrency.unwrap_or(defaults.page_concurrency),
+            download_concurrency: network.download_concurrency.unwrap_or(defaults.download_concurrency),
+            retries: network.retries.unwrap_or(defaults.retries),
+            single_file_folder: self.single_file_folder.unwrap_or(defaults.single_file_folder),
+            duplicate_action: self.duplicate_action(),
+        })
+    }
+
+    fn source_filter(&self) -> Result<SourceFilter, String> {
+        let filter = &self.filter;
+        let kinds = |kinds: &Option<Vec<String>>| SourceFilter::parse_kinds(&kinds.clone().unwrap_or_default().join(","));
+        let extensions = |extensions: &Option<Vec<String>>| {
+            SourceFilter::parse_extensions(&extensions.clone().unwrap_or_default().join(","))
+        };
+        Ok(SourceFilter {
+            include_kinds: kinds(&filter.only)?,
+            exclude_kinds: kinds(&filter.exclude_kinds)?,
+            include_extensions: extensions(&filter.extensions),
+            exclude_extensions: extensions(&filter.exclude_extensions),
+            min_size: filter.min_size.as_ref().map(Size::bytes).transpose()?,
+            max_size: filter.max_size.as_ref().map(Size::bytes).transpose()?,
+            name_pattern: filter.name.clone(),
+            exclude_name_pattern: filter.exclude_name.clone(),
+        })
+    }
+}
+
+/// 逗号分隔的列表，去掉空项
+pub fn split_list(text: &str) -> Vec<String> {
+    text.split(',').map(str::trim).filter(|item| !item.is_empty()).map(String::from).collect()
+}
+
+/// 把 "Name: value" 形式的请求头转成配置中的表
+pub fn header_map(lines: &[String]) -> Result<BTreeMap<String, String>, String> {
+    Ok(HttpConfig::parse_headers(&lines.join("\n"))?.into_iter().collect())
+}
+
+fn parse_number<T: FromStr>(var: Option<(String, String)>) -> Result<Option<T>, String> {
+    var.map(|(name, value)| {
+        value
+            .trim()
+            .parse()
+            .map_err(|_| format!("{}: expected a whole number, got {:?}", name, value))
+    })
+    .transpose()
+}
+
+fn parse_bool(var: Option<(String, String)>) -> Result<Option<bool>, String> {
+    var.map(|(name, value)| match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(format!("{}: expected true or false, got {:?}", name, value)),
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> Result<Config, String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Config::from_env(|name| vars.get(name).cloned())
+    }
+
+    fn parse(text: &str) -> Config {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let user = parse("dir = \"/user\"\nhistory = false\n[network]\nretries = 1\nmax_pages = 10");
+        let project = parse("[network]\nretries = 2");
+        let environment = env(&[("BUNKR_SPIDER_RETRIES", "3"), ("BUNKR_SPIDER_HISTORY", "yes")]).unwrap();
+        let cli = Config { dir: Some("/cli".to_string()), ..Default::default() };
+
+        let config = user.merge(project).merge(environment).merge(cli);
+        assert_eq!(config.dir.as_deref(), Some("/cli"));
+        assert_eq!(config.network.retries, Some(3));
+        assert_eq!(config.network.max_pages, Some(10));
+        assert_eq!(config.history, Some(true));
+
+        let client = config.client_config().unwrap();
+        assert_eq!(client.retries, 3);
+        assert_eq!(client.max_pages, 10);
+        assert_eq!(client.page_concurrency, ClientConfig::default().page_concurrency);
+    }
+
+    #[test]
+    fn headers_are_merged_by_name() {
+        let file = parse("[network.headers]\nAuthorization = \"old\"\nX-Keep = \"1\"");
+        let cli = Config {
+            network: NetworkConfig {
+                headers: header_map(&["Authorization: new".to_string(), "X-Extra: 2".to_string()]).unwrap(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let headers = file.merge(cli).network.headers;
+        assert_eq!(headers.len(), 3);
+        assert_eq!(headers["Authorization"], "new");
+        assert_eq!(headers["X-Keep"], "1");
+        assert_eq!(headers["X-Extra"], "2");
+    }
+
+    #[test]
+    fn environment_values_are_parsed_and_named_in_errors() {
+        let config = env(&[
+            ("BUNKR_SPIDER_ONLY", "video, image"),
+            ("BUNKR_SPIDER_DUPLICATES", "Skip"),
+            ("BUNKR_SPIDER_LANGUAGE", "zh_TW.UTF-8"),
+            ("BUNKR_SPIDER_DIR", "  "),
+        ])
+        .unwrap();
+        assert_eq!(config.filter.only, Some(vec!["video".to_string(), "image".to_string()]));
+        assert_eq!(config.duplicates, Some(DuplicateAction::Skip));
+        assert_eq!(config.language, Some(Language::TraditionalChinese));
+        assert_eq!(config.dir, None);
+
+        let error = env(&[("BUNKR_SPIDER_RETRIES", "many")]).unwrap_err();
+        assert_eq!(error, "BUNKR_SPIDER_RETRIES: expected a whole number, got \"many\"");
+        let error = env(&[("BUNKR_SPIDER_HISTORY", "maybe")]).unwrap_err();
+        assert_eq!(error, "BUNKR_SPIDER_HISTORY: expected true or false, got \"maybe\"");
+        let error = env(&[("BUNKR_SPIDER_DUPLICATES", "move")]).unwrap_err();
+        assert_eq!(error, "BUNKR_SPIDER_DUPLICATES: expected one of download, skip, hardlink, got \"move\"");
+    }
+
+    #[test]
+    fn validation_reports_every_problem_with_its_key() {
+        let config = parse(
+            "[network]\nretries = 99\npage_concurrency = 0\n[filter]\nonly = [\"movie\"]\nmin_size = \"2M\"\nmax_size = \"1M\"\nname = \"(\"",
+        );
+        let error = config.check("test.toml").unwrap_err();
+        assert!(error.starts_with("invalid configuration in test.toml:\n"), "{}", error);
+        for key in ["network.page_concurrency", "network.retries", "filter.only", "filter.min_size", "filter.name"] {
+            assert!(error.contains(&format!("\n  {}: ", key)), "{} not reported in:\n{}", key, error);
+        }
+
+        assert!(toml::from_str::<Config>("[network]\nretry = 1").is_err());
+    }
+
+    #[test]
+    fn relative_paths_are_resolved_against_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("bunkr-spider-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("cookies.txt"), "").unwrap();
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, "dir = \"downloads\"\n[network]\ncookies = \"cookies.txt\"").unwrap();
+
+        let config = Config::from_file(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let config = config.unwrap();
+        assert_eq!(config.dir, Some(dir.join("downloads").to_string_lossy().to_string()));
+        assert_eq!(config.network.cookies, Some(dir.join("cookies.txt")));
+    }
+}
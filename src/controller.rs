@@ -0,0 +1,386 @@
+use crate::bunkr::{BunkrSpider, BunkrSpiderInformation, BunkrSpiderState, RetryOptions, SharedInfo, Source, SpiderControl};
+use crate::client::BunkrClient;
+use crate::export::{self, ExportFormat};
+use crate::log_t;
+use crate::verify;
+use crate::watch::{sync_due, WatchList};
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+
+/// 一个任务分析完之后做什么
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobMode {
+    /// 先分析，再下载全部文件
+    Download,
+    /// 边分析边下载
+    Pipelined,
+    /// 分析并探测大小后停在挑选阶段，等待 `Command::DownloadSelected`
+    Review,
+    /// 只分析不下载，结束后由界面导出链接
+    DryRun { probe_sizes: bool },
+}
+
+/// 交给控制器运行的一组链接，按顺序逐个处理
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub urls: Vec<String>,
+    pub base_dir: String,
+    pub mode: JobMode,
+    /// 每个链接下载结束后删除过小的无效文件
+    pub delete_invalid: bool,
+}
+
+pub enum Command {
+    Start { client: Box<BunkrClient>, job: Job },
+    /// 暂停正在运行的任务，不再请求新的页面和数据
+    Pause,
+    Resume,
+    Stop,
+    /// 用相同的设置重新运行上一个任务
+    Retry,
+    /// 在同一目录中只重新下载上次失败的文件
+    RetryFailed(RetryOptions),
+    /// 挑选结束，`selected` 与 `JobStatus::sources` 一一对应
+    DownloadSelected(Vec<bool>),
+    /// 回到空闲状态，清空上一次的结果
+    Reset,
+    /// 同步关注列表中到期（`force` 时为全部）的专辑
+    SyncWatched { client: Box<BunkrClient>, list: Arc<Mutex<WatchList>>, force: bool },
+    /// 按 manifest 校验一个专辑文件夹，停止时中断
+    Verify(PathBuf),
+}
+
+/// 控制器发布的任务状态，进度计数仍在 `BunkrSpiderInformation` 中
+#[derive(Clone, Debug, Default)]
+pub struct JobStatus {
+    /// 是否有任务正在运行，运行期间只接受暂停、继续和停止
+    pub busy: bool,
+    pub paused: bool,
+    /// 正在处理第几个链接（从 1 开始）
+    pub current_url: usize,
+    pub url_count: usize,
+    pub album_url: String,
+    pub title: String,
+    /// 专辑文件夹
+    pub download_dir: String,
+    /// 分析出的文件，挑选和导出时使用
+    pub sources: Arc<Vec<Source>>,
+}
+
+impl JobStatus {
+    /// 按指定格式导出分析出的文件列表
+    pub fn export(&self, format: ExportFormat) -> String {
+        export::export(&self.sources, &self.download_dir, format)
+    }
+
+    pub fn export_file_name(&self, format: ExportFormat) -> String {
+        export::file_name(&self.title, format)
+    }
+}
+
+/// 任务控制器：独占一个爬虫，在当前 tokio 运行时的后台任务中按命令运行。
+/// 界面只发送命令、读取状态，不会被正在进行的分析或下载阻塞。
+/// 克隆得到的句柄控制同一个任务。
+#[derive(Clone)]
+pub struct Controller {
+    commands: mpsc::UnboundedSender<Command>,
+    status: watch::Receiver<JobStatus>,
+    info: SharedInfo,
+}
+
+impl Controller {
+    /// 启动控制器任务，必须在 tokio 运行时中调用
+    pub fn spawn() -> Controller {
+        let spider = BunkrSpider::new();
+        let info = spider.shared_info();
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let (publisher, status) = watch::channel(JobStatus::default());
+
+        tokio::spawn(run(spider, receiver, publisher));
+
+        Controller { commands, status, info }
+    }
+
+    pub fn send(&self, command: Command) {
+        if self.commands.send(command).is_err() {
+            log_t!("log.controller_not_running");
+        }
+    }
+
+    pub fn start(&self, client: BunkrClient, job: Job) {
+        self.send(Command::Start { client: Box::new(client), job });
+    }
+
+    pub fn pause(&self) {
+        self.send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        self.send(Command::Resume);
+    }
+
+    pub fn stop(&self) {
+        self.send(Command::Stop);
+    }
+
+    pub fn retry(&self) {
+        self.send(Command::Retry);
+    }
+
+    pub fn retry_failed(&self, options: RetryOptions) {
+        self.send(Command::RetryFailed(options));
+    }
+
+    pub fn download_selected(&self, selected: Vec<bool>) {
+        self.send(Command::DownloadSelected(selected));
+    }
+
+    pub fn reset(&self) {
+        self.send(Command::Reset);
+    }
+
+    pub fn sync_watched(&self, client: BunkrClient, list: Arc<Mutex<WatchList>>, force: bool) {
+        self.send(Command::SyncWatched { client: Box::new(client), list, force });
+    }
+
+    pub fn verify(&self, dir: PathBuf) {
+        self.send(Command::Verify(dir));
+    }
+
+    pub fn status(&self) -> JobStatus {
+        self.status.borrow().clone()
+    }
+
+    /// 进度计数和爬虫状态的快照
+    pub fn info(&self) -> BunkrSpiderInformation {
+        self.info.get()
+    }
+}
+
+/// 控制器任务的主循环，发送端全部 drop 后结束
+async fn run(
+    mut spider: BunkrSpider,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+    status: watch::Sender<JobStatus>,
+) {
+    let control = spider.control();
+    let mut last: Option<(BunkrClient, Job)> = None;
+
+    while let Some(command) = commands.recv().await {
+        match command {
+            Command::Start { client, job } => {
+                let client = *client;
+                last = Some((client.clone(), job.clone()));
+                let work = run_job(&mut spider, &status, client, job);
+                drive(work, &mut commands, &control, &status).await;
+            }
+            Command::Retry => {
+                let Some((client, job)) = last.clone() else {
+                    log_t!("log.nothing_to_retry");
+                    continue;
+                };
+                log_t!("log.retrying_last_job");
+                let work = run_job(&mut spider, &status, client, job);
+                drive(work, &mut commands, &control, &status).await;
+            }
+            Command::RetryFailed(options) => {
+                if !spider.get_state().is_terminal() {
+                    continue;
+                }
+                let delete_invalid = last.as_ref().is_some_and(|(_, job)| job.delete_invalid);
+                let work = retry_failed(&mut spider, options, delete_invalid);
+                drive(work, &mut commands, &control, &status).await;
+            }
+            Command::DownloadSelected(selected) => {
+                if spider.get_state() != BunkrSpiderState::Reviewing {
+                    continue;
+                }
+                let delete_invalid = last.as_ref().is_some_and(|(_, job)| job.delete_invalid);
+                let work = download_selected(&mut spider, &status, selected, delete_invalid);
+                drive(work, &mut commands, &control, &status).await;
+            }
+            Command::Reset => {
+                spider.reset();
+                status.send_replace(JobStatus::default());
+            }
+            Command::SyncWatched { client, list, force } => {
+                let work = sync_watched(&mut spider, &status, *client, list, force);
+                drive(work, &mut commands, &control, &status).await;
+            }
+            Command::Verify(dir) => {
+                control.clear();
+                let work = verify_folder(dir, control.clone());
+                drive(work, &mut commands, &control, &status).await;
+            }
+            // 没有任务在运行
+            Command::Pause | Command::Resume | Command::Stop => {}
+        }
+    }
+}
+
+/// 运行一段工作，同时继续处理命令；运行期间的新任务会被忽略
+async fn drive(
+    work: impl Future<Output = ()>,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    control: &SpiderControl,
+    status: &watch::Sender<JobStatus>,
+) {
+    status.send_modify(|status| status.busy = true);
+    let mut work = std::pin::pin!(work);
+
+    loop {
+        tokio::select! {
+            () = &mut work => break,
+            command = commands.recv() => match command {
+                Some(Command::Pause) => {
+                    control.pause();
+                    status.send_modify(|status| status.paused = true);
+                    log_t!("log.paused");
+                }
+                Some(Command::Resume) => {
+                    control.resume();
+                    status.send_modify(|status| status.paused = false);
+                    log_t!("log.resumed");
+                }
+                Some(Command::Stop) => {
+                    control.stop();
+                    control.resume();
+                    status.send_modify(|status| status.paused = false);
+                    log_t!("log.stop_sent");
+                }
+                Some(_) => log_t!("log.job_running"),
+                None => {
+                    work.await;
+                    break;
+                }
+            },
+        }
+    }
+
+    status.send_modify(|status| {
+        status.busy = false;
+        status.paused = false;
+    });
+}
+
+/// 依次处理任务中的链接，挑选和试运行只处理第一个
+async fn run_job(spider: &mut BunkrSpider, status: &watch::Sender<JobStatus>, client: BunkrClient, job: Job) {
+    let url_count = job.urls.len();
+    if url_count == 0 {
+        log_t!("log.no_url");
+        return;
+    }
+
+    for (index, url) in job.urls.into_iter().enumerate() {
+        if index > 0 && spider.is_stopped() {
+            log_t!("log.skipping_jobs", count = url_count - index);
+            return;
+        }
+        spider.reset();
+        client.configure(spider);
+        status.send_modify(|status| {
+            status.current_url = index + 1;
+            status.url_count = url_count;
+        });
+        if url_count > 1 {
+            log_t!("log.job_progress", current = index + 1, total = url_count, url = url);
+        }
+
+        if job.mode == JobMode::Pipelined {
+            spider.run_pipelined(job.base_dir.clone(), url).await.ok();
+        } else {
+            spider.run(job.base_dir.clone(), url).await;
+        }
+
+        match job.mode {
+            JobMode::DryRun { probe_sizes } => {
+                if probe_sizes {
+                    spider.probe_sizes().await;
+                }
+                spider.finish_dry_run();
+                publish(spider, status);
+                return;
+            }
+            JobMode::Review => {
+                spider.probe_sizes().await;
+                spider.begin_review();
+                publish(spider, status);
+                return;
+            }
+            JobMode::Download if !spider.is_loaded() => spider.finish_not_loaded(),
+            JobMode::Download => {
+                publish(spider, status);
+                spider.download_all().await.ok();
+            }
+            JobMode::Pipelined => {}
+        }
+        publish(spider, status);
+
+        if job.delete_invalid {
+            spider.clean_error_files().await;
+        }
+    }
+}
+
+async fn download_selected(
+    spider: &mut BunkrSpider,
+    status: &watch::Sender<JobStatus>,
+    selected: Vec<bool>,
+    delete_invalid: bool,
+) {
+    spider.retain_sources(&selected);
+    publish(spider, status);
+    spider.download_all().await.ok();
+
+    if delete_invalid {
+        spider.clean_error_files().await;
+    }
+}
+
+async fn retry_failed(spider: &mut BunkrSpider, options: RetryOptions, delete_invalid: bool) {
+    if let Err(e) = spider.retry_failed(options).await {
+        log_t!("log.retry_error", error = e);
+    }
+
+    if delete_invalid {
+        spider.clean_error_files().await;
+    }
+}
+
+async fn sync_watched(
+    spider: &mut BunkrSpider,
+    status: &watch::Sender<JobStatus>,
+    client: BunkrClient,
+    list: Arc<Mutex<WatchList>>,
+    force: bool,
+) {
+    spider.reset();
+    client.configure(spider);
+    sync_due(&list, spider, force).await;
+    publish(spider, status);
+}
+
+async fn verify_folder(dir: PathBuf, control: SpiderControl) {
+    log_t!("log.verifying", path = dir.display());
+    let report = tokio::select! {
+        report = verify::verify_dir(&dir) => report,
+        () = control.wait_until_stopped() => return,
+    };
+    match report {
+        Ok(report) => report.print(&dir),
+        Err(e) => log_t!("log.verification_failed", error = e),
+    }
+}
+
+/// 发布爬虫当前的分析结果
+fn publish(spider: &BunkrSpider, status: &watch::Sender<JobStatus>) {
+    status.send_modify(|status| {
+        status.album_url = spider.album_url().to_string();
+        status.title = spider.title().to_string();
+        status.download_dir = spider.get_download_dir();
+        status.sources = Arc::new(spider.sources().to_vec());
+    });
+}
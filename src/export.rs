@@ -0,0 +1,218 @@
+use crate::bunkr::Source;
+use crate::media::MediaKind;
+use crate::resolve::Resolution;
+
+use serde::{Deserialize, Serialize};
+
+/// 试运行（只分析不下载）时导出链接的格式
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// 每行一个直链
+    Urls,
+    /// aria2c `--input-file`，带 `out=`、`dir=` 和 Referer
+    Aria2,
+    /// 用 curl 下载的 shell 脚本
+    Curl,
+    /// 用 wget 下载的 shell 脚本
+    Wget,
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 6] = [
+        ExportFormat::Urls,
+        ExportFormat::Aria2,
+        ExportFormat::Curl,
+        ExportFormat::Wget,
+        ExportFormat::Json,
+        ExportFormat::Csv,
+    ];
+
+    pub fn parse(text: &str) -> Option<ExportFormat> {
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.label().eq_ignore_ascii_case(text.trim()))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Urls => "urls",
+            ExportFormat::Aria2 => "aria2",
+            ExportFormat::Curl => "curl",
+            ExportFormat::Wget => "wget",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    /// 保存对话框中建议的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Urls | ExportFormat::Aria2 => "txt",
+            ExportFormat::Curl | ExportFormat::Wget => "sh",
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    /// 是否会输出文件大小，需要先探测
+    pub fn needs_size(&self) -> bool {
+        matches!(self, ExportFormat::Json | ExportFormat::Csv)
+    }
+}
+
+#[derive(Serialize)]
+struct ExportEntry<'a> {
+    title: &'a str,
+    file_name: String,
+    url: &'a str,
+    page_url: &'a str,
+    kind: MediaKind,
+    resolution: Resolution,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+}
+
+/// 把分析出的文件列表导出为文本，`download_dir` 是这些文件本应保存到的目录
+pub fn export(sources: &[Source], download_dir: &str, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Urls => sources.iter().map(|s| format!("{}\n", s.url)).collect(),
+        ExportFormat::Aria2 => sources
+            .iter()
+            .map(|s| {
+                format!(
+                    "{}\n  dir={}\n  out={}\n  referer={}\n",
+                    s.url,
+                    download_dir,
+                    s.file_name(),
+                    s.page_url
+                )
+            })
+            .collect(),
+        ExportFormat::Curl | ExportFormat::Wget => shell_script(sources, download_dir, format),
+        ExportFormat::Json => {
+            let entries: Vec<ExportEntry> = sources.iter().map(entry).collect();
+            serde_json::to_string_pretty(&entries).unwrap_or_default() + "\n"
+        }
+        ExportFormat::Csv => {
+            let mut csv = String::from("title,file_name,url,page_url,kind,resolution,size\n");
+            for source in sources {
+                let fields = [
+                    csv_field(&source.title),
+                    csv_field(&source.file_name()),
+                    csv_field(&source.url),
+                    csv_field(&source.page_url),
+                    MediaKind::from_file_name(&source.title).label().to_string(),
+                    source.resolution.label().to_string(),
+                    source.size.map(|size| size.to_string()).unwrap_or_default(),
+                ];
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+    }
+}
+
+/// 导出时建议的文件名，专辑没有标题时用默认文件夹名
+pub fn file_name(title: &str, format: ExportFormat) -> String {
+    let title = if title.is_empty() { crate::bunkr::DEFAULT_DOWNLOAD_DIR } else { title };
+    format!("{}.{}", title, format.extension())
+}
+
+fn entry(source: &Source) -> ExportEntry<'_> {
+    ExportEntry {
+        title: &source.title,
+        file_name: source.file_name(),
+        url: &source.url,
+        page_url: &source.page_url,
+        kind: MediaKind::from_file_name(&source.title),
+        resolution: source.resolution,
+        size: source.size,
+    }
+}
+
+fn shell_script(sources: &[Source], download_dir: &str, format: ExportFormat) -> String {
+    let mut script = String::from("#!/bin/sh\nset -e\n\n");
+    script.push_str(&format!("mkdir -p {}\n", shell_quote(download_dir)));
+
+    for source in sources {
+        let path = format!("{}/{}", download_dir, source.file_name());
+        let line = if format == ExportFormat::Wget {
+            format!(
+                "wget --referer={} -O {} {}\n",
+                shell_quote(&source.page_url),
+                shell_quote(&path),
+                shell_quote(&source.url)
+            )
+        } else {
+            format!(
+                "curl -fL --retry 3 -e {} -o {} {}\n",
+                shell_quote(&source.page_url),
+                shell_quote(&path),
+                shell_quote(&source.url)
+            )
+        };
+        script.push_str(&line);
+    }
+    script
+}
+
+/// 用单引号包裹，内部的单引号写成 '\''
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(title: &str) -> Source {
+        Source {
+            title: title.to_string(),
+            url: "https://kebab.bunkr.ru/abc.mp4".to_string(),
+            page_url: "https://bunkr.cr/f/abc".to_string(),
+            resolution: Resolution::MediaSource,
+            size: Some(42),
+        }
+    }
+
+    #[test]
+    fn shell_script_quotes_single_quotes() {
+        let script = export(&[source("it's here.mp4")], "/tmp/it's", ExportFormat::Curl);
+        assert!(script.contains(r"mkdir -p '/tmp/it'\''s'"));
+        assert!(script.contains(r"-o '/tmp/it'\''s/it'\''s here.mp4' 'https://kebab.bunkr.ru/abc.mp4'"));
+
+        let script = export(&[source("it's here.mp4")], "out", ExportFormat::Wget);
+        assert!(script.contains(r"wget --referer='https://bunkr.cr/f/abc' -O 'out/it'\''s here.mp4' "));
+    }
+
+    #[test]
+    fn csv_escapes_commas_and_quotes() {
+        let csv = export(&[source(r#"a, "b".mp4"#)], "out", ExportFormat::Csv);
+        let row = csv.lines().nth(1).unwrap();
+        assert_eq!(
+            row,
+            r#""a, ""b"".mp4","a, _b_.mp4",https://kebab.bunkr.ru/abc.mp4,https://bunkr.cr/f/abc,video,media_source,42"#
+        );
+    }
+
+    #[test]
+    fn aria2_lists_dir_and_out() {
+        let text = export(&[source("a/b.mp4")], "/data/Album", ExportFormat::Aria2);
+        assert_eq!(
+            text,
+            "https://kebab.bunkr.ru/abc.mp4\n  dir=/data/Album\n  out=a_b.mp4\n  referer=https://bunkr.cr/f/abc\n"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::bunkr::Source;
+use crate::media::{self, MediaKind};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// 对分析出的文件做筛选。列表为空表示不限制；大小未知的文件不受大小条件影响。
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceFilter {
+    pub include_kinds: Vec<MediaKind>,
+    pub exclude_kinds: Vec<MediaKind>,
+    /// 只保留这些扩展名（小写，不含点）
+    pub include_extensions: Vec<String>,
+    pub exclude_extensions: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<u64>,
+    /// 文件名需匹配的正则
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_pattern: Option<String>,
+    /// 文件名匹配则排除的正则
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_name_pattern: Option<String>,
+}
+
+/// 编译好正则的筛选器，避免每个文件重新编译
+pub struct CompiledFilter {
+    filter: SourceFilter,
+    name: Option<Regex>,
+    exclude_name: Option<Regex>,
+}
+
+impl SourceFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == SourceFilter::default()
+    }
+
+    /// 是否需要先用 HEAD 探测文件大小
+    pub fn needs_size(&self) -> bool {
+        self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    pub fn compile(&self) -> Result<CompiledFilter, regex::Error> {
+        let compile = |pattern: &Option<String>| {
+            pattern
+                .as_deref()
+                .filter(|p| !p.is_empty())
+                .map(Regex::new)
+                .transpose()
+        };
+        Ok(CompiledFilter {
+            name: compile(&self.name_pattern)?,
+            exclude_name: compile(&self.exclude_name_pattern)?,
+            filter: self.clone(),
+        })
+    }
+
+    /// 解析逗号或空白分隔的扩展名列表，去掉点并转为小写
+    pub fn parse_extensions(text: &str) -> Vec<String> {
+        text.split(|c: char| c == ',' || c.is_whitespace())
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .collect()
+    }
+
+    /// 解析逗号分隔的媒体类型列表，如 "image,video"
+    pub fn parse_kinds(text: &str) -> Result<Vec<MediaKind>, String> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(|k| MediaKind::parse(k).ok_or_else(|| format!("unknown media kind: {}", k)))
+            .collect()
+    }
+
+    /// 解析 "500K"、"2M"、"1G" 或纯字节数
+    pub fn parse_size(text: &str) -> Result<u64, String> {
+        let text = text.trim();
+        let (number, unit) = match text.find(|c: char| !c.is_ascii_digit() && c != '.') {
+            Some(pos) => text.split_at(pos),
+            None => (text, ""),
+        };
+        let multiplier = match unit.trim().to_uppercase().trim_end_matches('B') {
+            "" => 1.0,
+            "K" => 1024.0,
+            "M" => 1024.0 * 1024.0,
+            "G" => 1024.0 * 1024.0 * 1024.0,
+            _ => return Err(format!("invalid size: {}", text)),
+        };
+        number
+            .parse::<f64>()
+            .map(|n| (n * multiplier) as u64)
+            .map_err(|_| format!("invalid size: {}", text))
+    }
+}
+
+impl CompiledFilter {
+    pub fn matches(&self, source: &Source) -> bool {
+        let f = &self.filter;
+        let kind = MediaKind::from_file_name(&source.title);
+        let extension = media::extension_of(&source.title).to_lowercase();
+
+        if !f.include_kinds.is_empty() && !f.include_kinds.contains(&kind) {
+            return false;
+        }
+        if f.exclude_kinds.contains(&kind) {
+            return false;
+        }
+        if !f.include_extensions.is_empty() && !f.include_extensions.contains(&extension) {
+            return false;
+        }
+        if f.exclude_extensions.contains(&extension) {
+            return false;
+        }
+        if let Some(size) = source.size {
+            if f.min_size.is_some_and(|min| size < min) {
+                return false;
+            }
+            if f.max_size.is_some_and(|max| size > max) {
+                return false;
+            }
+        }
+        if self.name.as_ref().is_some_and(|re| !re.is_match(&source.title)) {
+            return false;
+        }
+        if self.exclude_name.as_ref().is_some_and(|re| re.is_match(&source.title)) {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::Resolution;
+
+    fn source(title: &str, size: Option<u64>) -> Source {
+        Source {
+            title: title.to_string(),
+            url: String::new(),
+            page_url: String::new(),
+            resolution: Resolution::MediaSource,
+            size,
+        }
+    }
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(SourceFilter::parse_size("500K"), Ok(500 * 1024));
+        assert_eq!(SourceFilter::parse_size("2MB"), Ok(2 * 1024 * 1024));
+        assert_eq!(SourceFilter::parse_size("1.5G"), Ok(1024 * 1024 * 1024 * 3 / 2));
+        assert_eq!(SourceFilter::parse_size("123"), Ok(123));
+        assert!(SourceFilter::parse_size("abc").is_err());
+    }
+
+    #[test]
+    fn unknown_size_passes_size_bounds() {
+        let filter = SourceFilter { min_size: Some(1000), max_size: Some(2000), ..Default::default() };
+        let filter = filter.compile().unwrap();
+        assert!(filter.matches(&source("clip.mp4", None)));
+        assert!(filter.matches(&source("clip.mp4", Some(1500))));
+        assert!(!filter.matches(&source("clip.mp4", Some(999))));
+        assert!(!filter.matches(&source("clip.mp4", Some(2001))));
+    }
+
+    #[test]
+    fn exclude_kind_wins_over_include() {
+        let filter = SourceFilter {
+            include_kinds: vec![MediaKind::Video, MediaKind::Image],
+            exclude_kinds: vec![MediaKind::Image],
+            ..Default::default()
+        };
+        let filter = filter.compile().unwrap();
+        assert!(filter.matches(&source("clip.mp4", None)));
+        assert!(!filter.matches(&source("photo.jpg", None)));
+        assert!(!filter.matches(&source("notes.zip", None)));
+    }
+}
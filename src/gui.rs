@@ -0,0 +1,1187 @@
+use crate::settings::Settings;
+use crate::{clipboard, egui_printer};
+
+use rust_spider::bunkr;
+use rust_spider::controller::{self, Controller};
+use rust_spider::i18n::{self, Language};
+use rust_spider::{client, export, filter, history, http, import, log_t, media, mirrors, t, watch};
+use eframe::egui;
+use std::{sync::Arc};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use rfd::{FileDialog};
+
+const FONT_PIXEL: f32 = 1.3;
+const BIG_BUTTON_SIZE: egui::Vec2 = egui::Vec2::new(100.0, 25.0);
+
+/// 窗口右下角的短暂提示，可撤销刚加入队列的链接
+struct Toast {
+    message: String,
+    undo: Vec<String>,
+    shown_at: Instant,
+}
+
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tab {
+    Download,
+    History,
+    Watch,
+}
+
+/// 设置修改后等待这么久没有新的修改再写入文件
+const SETTINGS_SAVE_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_WINDOW_SIZE: egui::Vec2 = egui::Vec2::new(400.0, 500.0);
+
+/// 历史页最多显示的条数
+const HISTORY_ROWS: usize = 500;
+
+struct Gui {
+    /// 在后台运行任务，界面只向它发送命令
+    controller: Controller,
+    tab: Tab,
+    history: Arc<Mutex<history::History>>,
+    history_query: String,
+    duplicate_action: history::DuplicateAction,
+    watch_list: Arc<std::sync::Mutex<watch::WatchList>>,
+    watch_url: String,
+    watch_interval: u64,
+    checked_auto_watch: bool,
+    scheduler: Option<watch::Scheduler>,
+    base_dir: String,
+    text_input_url: String,
+    /// 待运行的专辑/文件链接
+    jobs: import::JobQueue,
+    checked_watch_clipboard: bool,
+    clipboard_watcher: Option<clipboard::ClipboardWatcher>,
+    /// 复制时历史正被占用、还没查过是否下载过的链接，下一帧再处理
+    pending_clipboard: Vec<String>,
+    toast: Option<Toast>,
+    checked_delete_errorfile: bool,
+    checked_single_file_folder: bool,
+    checked_pipelined: bool,
+    checked_review: bool,
+    /// 试运行：只分析，结束后导出链接而不下载
+    checked_dry_run: bool,
+    export_format: export::ExportFormat,
+    /// 重试失败文件时同时下载的数量
+    retry_concurrency: usize,
+    checked_retry_switch_cdn: bool,
+    /// 挑选阶段的文件列表及勾选状态
+    review: Option<Vec<(bunkr::Source, bool)>>,
+    /// None 表示跟随系统语言
+    language: Option<Language>,
+    cookies_file: String,
+    /// 为空时使用系统代理
+    proxy: String,
+    custom_headers: String,
+    mirror_domains: String,
+    cdn_domains: String,
+    max_pages: usize,
+    analyze_concurrency: usize,
+    download_concurrency: usize,
+    filter_kinds: Vec<media::MediaKind>,
+    filter_exclude_kinds: Vec<media::MediaKind>,
+    filter_extensions: String,
+    filter_exclude_extensions: String,
+    /// 0 表示不限制
+    filter_min_kb: u64,
+    filter_max_kb: u64,
+    filter_name: String,
+    filter_exclude_name: String,
+    /// 最近一次写入文件的设置
+    saved_settings: Settings,
+    /// 还没写入的设置及第一次发现改动的时间
+    unsaved_settings: Option<(Settings, Instant)>,
+}
+
+
+impl eframe::App for Gui {
+    // 每帧绘制UI的核心方法
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.persist_settings(ctx);
+        self.import_dropped_and_pasted(ctx);
+        self.import_from_clipboard(ctx);
+        self.show_toast(ctx);
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(t!("ui.heading"));
+
+            ui.hyperlink_to(t!("ui.website"), 
+            "https://laull.top/article/8bc5fd18-a180-4704-b8fd-c64497615472");
+
+            self.show_language(ui);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let download_label = t!("ui.tab_download");
+                let history_label = t!("ui.tab_history");
+                let watch_label = t!("ui.tab_watch");
+                ui.selectable_value(&mut self.tab, Tab::Download, download_label);
+                ui.selectable_value(&mut self.tab, Tab::History, history_label);
+                ui.selectable_value(&mut self.tab, Tab::Watch, watch_label);
+            });
+            if self.tab == Tab::History {
+                self.show_history(ui);
+                return;
+            }
+            if self.tab == Tab::Watch {
+                self.show_watch(ui);
+                self.show_log(ui);
+                return;
+            }
+
+            ui.horizontal(|ui|{      
+                ui.label(t!("ui.download_dir"));
+                if ui.button(t!("ui.select_folder")).clicked() {
+                    // 打开本地文件夹选择框
+                    let selected = FileDialog::new()
+                        .set_title(t!("ui.select_download_folder"))
+                        .pick_folder();
+
+                    self.base_dir = selected.map(
+                        |path| path.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                }
+                if ui.button(t!("ui.verify_album_folder")).clicked() {
+                    self.verify_folder();
+                }
+            });
+            ui.text_edit_singleline(&mut self.base_dir);
+
+            ui.label(t!("ui.album_or_file_url"));
+            ui.text_edit_singleline(&mut self.text_input_url);
+            // 粘贴了多个链接时全部移到任务队列
+            if import::extract_urls(&self.text_input_url, &self.mirror_config()).len() > 1 {
+                let text = std::mem::take(&mut self.text_input_url);
+                self.add_jobs(&text);
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(t!("ui.import_links")).clicked() {
+                    let selected = FileDialog::new()
+                        .set_title(t!("ui.select_link_files"))
+                        .add_filter("txt", &["txt"])
+                        .pick_files();
+                    for path in selected.unwrap_or_default() {
+                        self.import_file(&path);
+                    }
+                }
+                ui.label(t!("ui.drop_hint"));
+            });
+            let watch_label = t!("ui.watch_clipboard");
+            if ui.checkbox(&mut self.checked_watch_clipboard, watch_label).changed() {
+                self.toggle_clipboard_watcher();
+            }
+            self.show_jobs(ui);
+
+            let delete_invalid_label = t!("ui.delete_invalid");
+            ui.checkbox(&mut self.checked_delete_errorfile, delete_invalid_label);
+            let single_file_folder_label = t!("ui.single_file_folder");
+            ui.checkbox(&mut self.checked_single_file_folder, single_file_folder_label);
+            let pipelined_label = t!("ui.pipelined");
+            ui.checkbox(&mut self.checked_pipelined, pipelined_label);
+            ui.horizontal(|ui| {
+                ui.label(t!("ui.previously_downloaded"));
+                egui::ComboBox::from_id_salt("duplicate_action")
+                    .selected_text(self.duplicate_action_text(self.duplicate_action))
+                    .show_ui(ui, |ui| {
+                        for action in history::DuplicateAction::ALL {
+                            let text = self.duplicate_action_text(action);
+                            ui.selectable_value(&mut self.duplicate_action, action, text);
+                        }
+                    });
+            });
+            let review_label = t!("ui.review");
+            ui.checkbox(&mut self.checked_review, review_label);
+            ui.horizontal(|ui| {
+                let dry_run_label = t!("ui.dry_run");
+                ui.checkbox(&mut self.checked_dry_run, dry_run_label);
+                if self.checked_dry_run {
+                    egui::ComboBox::from_id_salt("export_format")
+                        .selected_text(self.export_format.label())
+                        .show_ui(ui, |ui| {
+                            for format in export::ExportFormat::ALL {
+                                ui.selectable_value(&mut self.export_format, format, format.label());
+                            }
+                        });
+                }
+            });
+
+            egui::CollapsingHeader::new(t!("ui.network_settings"))
+                .id_salt("network_settings")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.cookies_file"));
+                        if ui.button(t!("ui.import")).clicked() {
+                            let selected = FileDialog::new()
+                                .set_title(t!("ui.select_cookies"))
+                                .add_filter("cookies.txt", &["txt"])
+                                .pick_file();
+                            if let Some(path) = selected {
+                                self.cookies_file = path.to_string_lossy().into_owned();
+                            }
+                        }
+                        if ui.button(t!("ui.clear_cookies")).clicked() {
+                            self.cookies_file.clear();
+                        }
+                    });
+                    ui.text_edit_singleline(&mut self.cookies_file);
+
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.proxy"));
+                        ui.add(egui::TextEdit::singleline(&mut self.proxy).hint_text("http://127.0.0.1:8080"));
+                    });
+
+                    ui.label(t!("ui.custom_headers"));
+                    ui.text_edit_multiline(&mut self.custom_headers);
+
+                    ui.label(t!("ui.mirror_domains"));
+                    ui.text_edit_singleline(&mut self.mirror_domains);
+                    ui.label(t!("ui.cdn_domains"));
+                    ui.text_edit_singleline(&mut self.cdn_domains);
+
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.max_pages"));
+                        ui.add(egui::DragValue::new(&mut self.max_pages).range(1..=100_000));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.page_concurrency"));
+                        ui.add(egui::DragValue::new(&mut self.analyze_concurrency).range(1..=64));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.download_concurrency"));
+                        ui.add(egui::DragValue::new(&mut self.download_concurrency).range(1..=64));
+                    });
+                });
+
+            egui::CollapsingHeader::new(t!("ui.filters"))
+                .id_salt("filters")
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.filter_kinds"));
+                        for kind in media::MediaKind::ALL {
+                            let mut checked = self.filter_kinds.contains(&kind);
+                            if ui.checkbox(&mut checked, kind.label()).changed() {
+                                if checked {
+                                    self.filter_kinds.push(kind);
+                                } else {
+                                    self.filter_kinds.retain(|k| *k != kind);
+                                }
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.exclude_kinds"));
+                        for kind in media::MediaKind::ALL {
+                            let mut checked = self.filter_exclude_kinds.contains(&kind);
+                            if ui.checkbox(&mut checked, kind.label()).changed() {
+                                if checked {
+                                    self.filter_exclude_kinds.push(kind);
+                                } else {
+                                    self.filter_exclude_kinds.retain(|k| *k != kind);
+                                }
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.only_extensions"));
+                        ui.text_edit_singleline(&mut self.filter_extensions);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.exclude_extensions"));
+                        ui.text_edit_singleline(&mut self.filter_exclude_extensions);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.size_limit"));
+                        ui.add(egui::DragValue::new(&mut self.filter_min_kb).prefix("≥ "));
+                        ui.add(egui::DragValue::new(&mut self.filter_max_kb).prefix("≤ "));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.name_regex"));
+                        ui.text_edit_singleline(&mut self.filter_name);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label(t!("ui.exclude_name_regex"));
+                        ui.text_edit_singleline(&mut self.filter_exclude_name);
+                    });
+                });
+
+            // 获取当前状态
+            let info = self.controller.info();
+            let status = self.controller.status();
+            let state = info.state;
+            
+            // 运行中定时刷新，让进度计数随后台任务更新
+            if state != bunkr::BunkrSpiderState::Idle || status.busy {
+                ctx.request_repaint_after(std::time::Duration::from_millis(250));
+            }
+
+            // 显示进度信息
+            if matches!(state, bunkr::BunkrSpiderState::Analyzing | bunkr::BunkrSpiderState::Downloading)
+                && info.pages_discovered > 0
+                && info.pages_parsed < info.pages_discovered {
+                ui.label(t!("ui.pages_analyzed", parsed = info.pages_parsed, discovered = info.pages_discovered));
+            }
+            if let Some(total) = info.total_sources
+                && let Some(downloaded) = info.downloaded_sources {
+                let progress_percent = if total > 0 {
+                    (downloaded as f32 / total as f32 * 100.0) as u32
+                } else {
+                    0
+                };
+                ui.label(t!("ui.progress", downloaded = downloaded, total = total, percent = progress_percent));
+            }
+            if status.busy && status.url_count > 1 {
+                ui.label(t!("ui.job_progress", current = status.current_url, total = status.url_count));
+            }
+            ui.horizontal(|ui|{
+            if status.busy {
+                if ui.add_sized(BIG_BUTTON_SIZE, 
+                    egui::Button::new(t!("ui.stop")))
+                    .clicked() {
+                    self.controller.stop();
+                }
+                let pause_label = if status.paused {
+                    t!("ui.resume")
+                } else {
+                    t!("ui.pause")
+                };
+                if ui.add_sized(BIG_BUTTON_SIZE, egui::Button::new(pause_label)).clicked() {
+                    if status.paused {
+                        self.controller.resume();
+                    } else {
+                        self.controller.pause();
+                    }
+                }
+            }
+            else if state == bunkr::BunkrSpiderState::Idle {
+                if ui.add_sized(BIG_BUTTON_SIZE, 
+                    egui::Button::new(t!("ui.run")))
+                    .clicked() {
+                    let dry_run = self.checked_dry_run;
+                    let review = self.checked_review && !dry_run;
+                    let mode = if dry_run {
+                        controller::JobMode::DryRun { probe_sizes: self.export_format.needs_size() }
+                    } else if review {
+                        controller::JobMode::Review
+                    } else if self.checked_pipelined {
+                        controller::JobMode::Pipelined
+                    } else {
+                        controller::JobMode::Download
+                    };
+
+                    match self.client() {
+                        Ok(client) => {
+                            // 挑选和试运行需要界面参与，只取第一个任务，其余留在队列中
+                            let urls = self.take_jobs(review || dry_run);
+                            self.review = None;
+                            self.controller.start(client, controller::Job {
+                                urls,
+                                base_dir: self.base_dir.clone(),
+                                mode,
+                                delete_invalid: self.checked_delete_errorfile,
+                            });
+                        }
+                        Err(e) => log_t!("log.invalid_settings", error = e),
+                    }
+                }
+            }
+            else if state == bunkr::BunkrSpiderState::Reviewing {
+                ui.label(t!("ui.select_files_below"));
+            }
+            else if state.is_terminal() {
+                if ui.add_sized(BIG_BUTTON_SIZE, 
+                    egui::Button::new(t!("ui.new_download")))
+                    .clicked() {
+                    self.controller.reset();
+                }
+                if ui.add_sized(BIG_BUTTON_SIZE,
+                    egui::Button::new(t!("ui.run_again")))
+                    .clicked() {
+                    self.controller.retry();
+                }
+                if ui.add_sized(BIG_BUTTON_SIZE,
+                    egui::Button::new(t!("ui.export_links")))
+                    .clicked() {
+                    self.export_links(&status);
+                }
+            }
+
+            match state {
+                bunkr::BunkrSpiderState::Idle => {
+                    ui.label(t!("ui.state_idle"));
+                }
+                bunkr::BunkrSpiderState::Analyzing => {
+                    ui.label(t!("ui.state_analyzing"));
+                }
+                bunkr::BunkrSpiderState::Reviewing => {
+                    ui.label(t!("ui.state_reviewing"));
+                }
+                bunkr::BunkrSpiderState::Downloading => {
+                    ui.label(t!("ui.state_downloading"));
+                }
+                bunkr::BunkrSpiderState::Finished => {
+                    ui.label(t!("ui.state_finished"));
+                }
+                bunkr::BunkrSpiderState::PartiallyCompleted => {
+                    ui.colored_label(ui.visuals().warn_fg_color,
+                        t!("ui.state_partially_completed"));
+                }
+                bunkr::BunkrSpiderState::Failed => {
+                    ui.colored_label(ui.visuals().error_fg_color,
+                        t!("ui.state_failed"));
+                }
+                bunkr::BunkrSpiderState::Cancelled => {
+                    ui.label(t!("ui.state_cancelled"));
+                }
+            }
+            });
+
+            if state == bunkr::BunkrSpiderState::Reviewing && !status.busy {
+                self.show_review(ui, &status);
+            }
+            if state.is_terminal() && !info.failed.is_empty() {
+                self.show_failed(ui, &info.failed);
+            }
+            
+            self.show_log(ui);
+        });
+    }
+
+    // 关闭窗口时写入还没来得及保存的设置
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some((settings, _)) = self.unsaved_settings.take() {
+            self.save_settings(settings);
+        }
+    }
+}
+
+impl Gui {
+    // 按保存的设置初始化
+    fn new(_ctx: &egui::Context, settings: Settings) -> Self {
+
+        let custom_font_data = include_bytes!("../font/LXGWWenKaiLite-Regular.ttf");
+        let mut fonts = egui::FontDefinitions::default();
+        fonts.font_data.insert(
+            "CustomFont".to_string(),
+            egui::FontData::from_owned(custom_font_data.to_vec()).into(),
+        );
+
+        fonts
+            .families
+            .entry(egui::FontFamily::Proportional)
+            .or_default()
+            .insert(0, "CustomFont".to_string());
+
+        _ctx.set_fonts(fonts);
+
+        _ctx.set_pixels_per_point(FONT_PIXEL);
+        i18n::set_language(settings.language.unwrap_or_else(Language::detect));
+
+        let mut gui = Self {
+            controller: Controller::spawn(),
+            tab: Tab::Download,
+            history: Arc::new(Mutex::new(history::History::open_default())),
+            history_query: String::new(),
+            duplicate_action: settings.duplicate_action,
+            watch_list: Arc::new(std::sync::Mutex::new(watch::WatchList::load())),
+            watch_url: String::new(),
+            watch_interval: settings.watch_interval,
+            checked_auto_watch: settings.auto_watch,
+            scheduler: None,
+            text_input_url: String::new(),
+            jobs: import::JobQueue::default(),
+            checked_watch_clipboard: settings.watch_clipboard,
+            clipboard_watcher: None,
+            pending_clipboard: Vec::new(),
+            toast: None,
+            checked_delete_errorfile: settings.delete_invalid,
+            checked_single_file_folder: settings.single_file_folder,
+            checked_pipelined: settings.pipelined,
+            checked_review: settings.review,
+            checked_dry_run: false,
+            export_format: settings.export_format,
+            review: None,
+            retry_concurrency: settings.retry_concurrency,
+            checked_retry_switch_cdn: settings.retry_switch_cdn,
+            base_dir: settings.base_dir.clone(),
+            language: settings.language,
+            cookies_file: settings.cookies_file.clone(),
+            proxy: settings.proxy.clone(),
+            custom_headers: settings.custom_headers.clone(),
+            mirror_domains: settings.mirror_domains.clone(),
+            cdn_domains: settings.cdn_domains.clone(),
+            max_pages: settings.max_pages,
+            analyze_concurrency: settings.analyze_concurrency,
+            download_concurrency: settings.download_concurrency,
+            filter_kinds: settings.filter_kinds.clone(),
+            filter_exclude_kinds: settings.filter_exclude_kinds.clone(),
+            filter_extensions: settings.filter_extensions.clone(),
+            filter_exclude_extensions: settings.filter_exclude_extensions.clone(),
+            filter_min_kb: settings.filter_min_kb,
+            filter_max_kb: settings.filter_max_kb,
+            filter_name: settings.filter_name.clone(),
+            filter_exclude_name: settings.filter_exclude_name.clone(),
+            saved_settings: settings,
+            unsaved_settings: None,
+        };
+
+        // 上次开着的后台功能在启动时恢复
+        if gui.checked_watch_clipboard {
+            gui.toggle_clipboard_watcher();
+        }
+        if gui.checked_auto_watch {
+            gui.toggle_scheduler();
+        }
+        gui
+    }
+
+    /// 界面上当前的设置
+    fn settings(&self, ctx: &egui::Context) -> Settings {
+        let window_size = ctx
+            .input(|i| i.viewport().inner_rect)
+            .map(|rect| rect.size() * ctx.zoom_factor())
+            .map(|size| [size.x, size.y])
+            .or(self.saved_settings.window_size);
+        Settings {
+            base_dir: self.base_dir.clone(),
+            language: self.language,
+            delete_invalid: self.checked_delete_errorfile,
+            single_file_folder: self.checked_single_file_folder,
+            pipelined: self.checked_pipelined,
+            review: self.checked_review,
+            export_format: self.export_format,
+            duplicate_action: self.duplicate_action,
+            watch_clipboard: self.checked_watch_clipboard,
+            auto_watch: self.checked_auto_watch,
+            watch_interval: self.watch_interval,
+            cookies_file: self.cookies_file.clone(),
+            proxy: self.proxy.clone(),
+            custom_headers: self.custom_headers.clone(),
+            mirror_domains: self.mirror_domains.clone(),
+            cdn_domains: self.cdn_domains.clone(),
+            max_pages: self.max_pages,
+            analyze_concurrency: self.analyze_concurrency,
+            download_concurrency: self.download_concurrency,
+            retry_concurrency: self.retry_concurrency,
+            retry_switch_cdn: self.checked_retry_switch_cdn,
+            filter_kinds: self.filter_kinds.clone(),
+            filter_exclude_kinds: self.filter_exclude_kinds.clone(),
+            filter_extensions: self.filter_extensions.clone(),
+            filter_exclude_extensions: self.filter_exclude_extensions.clone(),
+            filter_min_kb: self.filter_min_kb,
+            filter_max_kb: self.filter_max_kb,
+            filter_name: self.filter_name.clone(),
+            filter_exclude_name: self.filter_exclude_name.clone(),
+            window_size,
+        }
+    }
+
+    /// 设置有变化且一段时间内没有再改动时写入配置文件
+    fn persist_settings(&mut self, ctx: &egui::Context) {
+        let settings = self.settings(ctx);
+        if settings == self.saved_settings {
+            self.unsaved_settings = None;
+            return;
+        }
+
+        let changed_at = self.unsaved_settings.as_ref().map_or_else(Instant::now, |(_, at)| *at);
+        if changed_at.elapsed() < SETTINGS_SAVE_DELAY {
+            self.unsaved_settings = Some((settings, changed_at));
+            ctx.request_repaint_after(SETTINGS_SAVE_DELAY);
+            return;
+        }
+        self.save_settings(settings);
+    }
+
+    fn save_settings(&mut self, settings: Settings) {
+        if let Err(e) = settings.save() {
+            log_t!("log.save_settings_failed", error = e);
+        }
+        self.saved_settings = settings;
+        self.unsaved_settings = None;
+    }
+
+    /// 挑选面板：列出分析出的文件，勾选后只下载选中的部分
+    fn show_review(&mut self, ui: &mut egui::Ui, status: &controller::JobStatus) {
+        if self.review.is_none() {
+            self.review = Some(status.sources.iter().cloned().map(|s| (s, true)).collect());
+        }
+        let Some(mut review) = self.review.take() else {
+            return;
+        };
+
+        let selected_count = review.iter().filter(|(_, checked)| *checked).count();
+        ui.horizontal(|ui| {
+            if ui.button(t!("ui.select_all")).clicked() {
+                review.iter_mut().for_each(|(_, checked)| *checked = true);
+            }
+            if ui.button(t!("ui.select_none")).clicked() {
+                review.iter_mut().for_each(|(_, checked)| *checked = false);
+            }
+            ui.label(format!("{}/{}", selected_count, review.len()));
+        });
+
+        egui::ScrollArea::vertical()
+            .id_salt("review_table")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                egui::Grid::new("review_grid").striped(true).show(ui, |ui| {
+                    ui.label("");
+                    ui.strong(t!("ui.name"));
+                    ui.strong(t!("ui.kind"));
+                    ui.strong(t!("ui.extension"));
+                    ui.strong(t!("ui.size"));
+                    ui.end_row();
+
+                    for (source, checked) in review.iter_mut() {
+                        ui.checkbox(checked, "");
+                        ui.label(&source.title);
+                        ui.label(media::MediaKind::from_file_name(&source.title).label());
+                        ui.label(media::extension_of(&source.title).to_lowercase());
+                        ui.label(source.size.map(media::format_size).unwrap_or_else(|| "?".to_string()));
+                        ui.end_row();
+                    }
+                });
+            });
+
+        let mut finished = false;
+        ui.horizontal(|ui| {
+            if ui.add_sized(BIG_BUTTON_SIZE,
+                egui::Button::new(t!("ui.download_selected")))
+                .clicked() {
+                let selected: Vec<bool> = review.iter().map(|(_, checked)| *checked).collect();
+                self.controller.download_selected(selected);
+                finished = true;
+            }
+            if ui.add_sized(BIG_BUTTON_SIZE,
+                egui::Button::new(t!("ui.cancel")))
+                .clicked() {
+                self.controller.reset();
+                finished = true;
+            }
+        });
+
+        if !finished {
+            self.review = Some(review);
+        }
+    }
+
+    /// 失败文件面板：列出下载失败的文件和最后一次的错误
+    fn show_failed(&mut self, ui: &mut egui::Ui, failed: &[bunkr::FailedSource]) {
+        egui::CollapsingHeader::new(t!("ui.failed_files", count = failed.len()))
+            .id_salt("failed_files")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.add_sized(BIG_BUTTON_SIZE,
+                        egui::Button::new(t!("ui.retry_failed")))
+                        .clicked() {
+                        self.controller.retry_failed(bunkr::RetryOptions {
+                            concurrency: Some(self.retry_concurrency),
+                            switch_cdn: self.checked_retry_switch_cdn,
+                        });
+                    }
+                    ui.label(t!("ui.concurrency"));
+                    ui.add(egui::DragValue::new(&mut self.retry_concurrency).range(1..=16));
+                    let switch_cdn_label = t!("ui.use_another_cdn");
+                    ui.checkbox(&mut self.checked_retry_switch_cdn, switch_cdn_label);
+                });
+
+                egui::ScrollArea::vertical()
+                    .id_salt("failed_table")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("failed_grid").striped(true).show(ui, |ui| {
+                            ui.strong(t!("ui.name"));
+                            ui.strong(t!("ui.error"));
+                            ui.end_row();
+
+                            for failure in failed {
+                                ui.hyperlink_to(&failure.source.title, &failure.source.page_url);
+                                ui.label(&failure.error);
+                                ui.end_row();
+                            }
+                        });
+                    });
+            });
+    }
+
+    /// 处理拖入窗口的文件和未聚焦输入框时粘贴的文本
+    fn import_dropped_and_pasted(&mut self, ctx: &egui::Context) {
+        let (dropped, pasted) = ctx.input(|i| {
+            let pasted: Vec<String> = i.events
+                .iter()
+                .filter_map(|e| match e {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+                .collect();
+            (i.raw.dropped_files.clone(), pasted)
+        });
+
+        for file in dropped {
+            if let Some(path) = &file.path {
+                self.import_file(path);
+            } else if let Some(bytes) = &file.bytes {
+                self.add_jobs(&String::from_utf8_lossy(bytes));
+            }
+        }
+
+        if ctx.memory(|m| m.focused().is_none()) {
+            for text in pasted {
+                self.add_jobs(&text);
+            }
+        }
+    }
+
+    fn toggle_scheduler(&mut self) {
+        self.scheduler = None;
+        if !self.checked_auto_watch {
+            return;
+        }
+        match self.client() {
+            Ok(client) => {
+                let scheduler = watch::Scheduler::start(self.watch_list.clone(), self.controller.clone(), client);
+                self.scheduler = Some(scheduler);
+            }
+            Err(e) => {
+                log_t!("log.invalid_settings", error = e);
+                self.checked_auto_watch = false;
+            }
+        }
+    }
+
+    fn toggle_clipboard_watcher(&mut self) {
+        if !self.checked_watch_clipboard {
+            self.clipboard_watcher = None;
+            return;
+        }
+        match clipboard::ClipboardWatcher::start() {
+            Ok(watcher) => self.clipboard_watcher = Some(watcher),
+            Err(e) => {
+                log_t!("log.clipboard_failed", error = e);
+                self.checked_watch_clipboard = false;
+            }
+        }
+    }
+
+    /// 把新复制的 bunkr 链接加入队列，已经进过队列的忽略
+    fn import_from_clipboard(&mut self, ctx: &egui::Context) {
+        let Some(watcher) = &self.clipboard_watcher else {
+            return;
+        };
+        ctx.request_repaint_after(clipboard::POLL_INTERVAL);
+
+        let mirrors = self.mirror_config();
+        let copied: Vec<String> = watcher
+            .take_copied()
+            .iter()
+            .flat_map(|text| import::extract_urls(text, &mirrors))
+            .collect();
+        self.pending_clipboard.extend(copied);
+        if self.pending_clipboard.is_empty() {
+            return;
+        }
+        // 历史正在被下载任务写入时先留着，下次轮询再查，不跳过检查
+        let urls: Vec<String> = match self.history.try_lock() {
+            Ok(history) => std::mem::take(&mut self.pending_clipboard)
+                .into_iter()
+                .filter(|url| !history.contains_url(url))
+                .collect(),
+            Err(_) => return,
+        };
+        let added = self.jobs.add_unseen(urls);
+        if added.is_empty() {
+            return;
+        }
+
+        let message = if added.len() == 1 {
+            t!("ui.queued_one", url = added[0])
+        } else {
+            t!("ui.queued_many", count = added.len())
+        };
+        self.toast = Some(Toast {
+            message,
+            undo: added,
+            shown_at: Instant::now(),
+        });
+    }
+
+    fn show_toast(&mut self, ctx: &egui::Context) {
+        let Some(toast) = &self.toast else {
+            return;
+        };
+        let remaining = TOAST_DURATION.saturating_sub(toast.shown_at.elapsed());
+        if remaining.is_zero() {
+            self.toast = None;
+            return;
+        }
+        ctx.request_repaint_after(remaining);
+
+        let undo_label = t!("ui.undo");
+        let mut undo = false;
+        egui::Area::new(egui::Id::new("toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(&toast.message);
+                        undo = ui.button(undo_label).clicked();
+                    });
+                });
+            });
+
+        if undo && let Some(toast) = self.toast.take() {
+            for url in &toast.undo {
+                self.jobs.remove_url(url);
+            }
+        }
+    }
+
+    fn import_file(&mut self, path: &std::path::Path) {
+        match import::read_url_file(path, &self.mirror_config()) {
+            Ok(urls) => {
+                let found = urls.len();
+                let added = self.jobs.add(urls);
+                log_t!("log.imported_links", added = added, found = found, path = path.display());
+            }
+            Err(e) => log_t!("log.read_failed", path = path.display(), error = e),
+        }
+    }
+
+    /// 从文本中提取 bunkr 链接加入队列，返回新加入的数量
+    fn add_jobs(&mut self, text: &str) -> usize {
+        let urls = import::extract_urls(text, &self.mirror_config());
+        let found = urls.len();
+        let added = self.jobs.add(urls);
+        if found > 0 {
+            log_t!("log.queued_links", added = added, skipped = found - added);
+        }
+        added
+    }
+
+    /// 取出本次要运行的链接：输入框中的链接在前，`first_only` 时只取一个
+    fn take_jobs(&mut self, first_only: bool) -> Vec<String> {
+        let typed = self.text_input_url.trim().to_string();
+        if !typed.is_empty() {
+            let queued = if first_only { Vec::new() } else { self.jobs.drain() };
+            let rest = queued.into_iter().filter(|u| *u != typed);
+            return std::iter::once(typed.clone()).chain(rest).collect();
+        }
+        if first_only {
+            return self.jobs.pop_front().into_iter().collect();
+        }
+        self.jobs.drain()
+    }
+
+    /// 任务队列：列出等待运行的链接，可单独移除或清空
+    fn show_jobs(&mut self, ui: &mut egui::Ui) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let title = t!("ui.job_queue", count = self.jobs.len());
+        let clear_label = t!("ui.clear_queue");
+        let mut removed = None;
+        egui::CollapsingHeader::new(title)
+            .id_salt("job_queue")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("job_queue_list")
+                    .max_height(120.0)
+                    .show(ui, |ui| {
+                        for (index, url) in self.jobs.urls().iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✕").clicked() {
+                                    removed = Some(index);
+                                }
+                                ui.label(url);
+                            });
+                        }
+                    });
+                if ui.button(clear_label).clicked() {
+                    self.jobs.clear();
+                }
+            });
+        if let Some(index) = removed {
+            self.jobs.remove(index);
+        }
+    }
+
+    /// 语言下拉框，切换后界面和之后的日志立即使用新语言
+    fn show_language(&mut self, ui: &mut egui::Ui) {
+        let system_label = format!("{} ({})", t!("ui.language_system"), Language::detect().name());
+        ui.horizontal(|ui| {
+            ui.label(t!("ui.language"));
+            let selected = self.language.map_or(system_label.clone(), |language| language.name().to_string());
+            let mut changed = false;
+            egui::ComboBox::from_id_salt("language")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    changed |= ui.selectable_value(&mut self.language, None, system_label).changed();
+                    for language in Language::ALL {
+                        changed |= ui.selectable_value(&mut self.language, Some(language), language.name()).changed();
+                    }
+                });
+            if changed {
+                i18n::set_language(self.language.unwrap_or_else(Language::detect));
+            }
+        });
+    }
+
+    fn show_log(&self, ui: &mut egui::Ui) {
+        ui.label(t!("ui.log_output"));
+        egui_printer::show(ui, Some(400.0), None);
+    }
+
+    /// 关注页：管理定期同步的专辑，可后台自动检查或立即检查
+    fn show_watch(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(t!("ui.watch_album_url"));
+            ui.text_edit_singleline(&mut self.watch_url);
+        });
+        ui.horizontal(|ui| {
+            ui.label(t!("ui.check_every"));
+            ui.add(egui::DragValue::new(&mut self.watch_interval).range(1..=10080));
+            if ui.button(t!("ui.watch")).clicked() {
+                let url = self.watch_url.trim().to_string();
+                if import::is_bunkr_url(&url, &self.mirror_config()) {
+                    let mut list = self.watch_list.lock().unwrap_or_else(|e| e.into_inner());
+                    list.add(&url, &self.base_dir, self.watch_interval);
+                    if let Err(e) = list.save() {
+                        log_t!("log.save_watch_list_failed", error = e);
+                    }
+                    self.watch_url.clear();
+                } else {
+                    log_t!("log.not_album_url", url = url);
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let auto_label = t!("ui.check_automatically");
+            if ui.checkbox(&mut self.checked_auto_watch, auto_label).changed() {
+                self.toggle_scheduler();
+            }
+            if ui.button(t!("ui.check_all_now")).clicked() {
+                match self.client() {
+                    Ok(client) => self.controller.sync_watched(client, self.watch_list.clone(), true),
+                    Err(e) => log_t!("log.invalid_settings", error = e),
+                }
+            }
+        });
+
+        let mut removed = None;
+        let remove_label = t!("ui.remove");
+        let never_label = t!("ui.never");
+        {
+            let list = self.watch_list.lock().unwrap_or_else(|e| e.into_inner());
+            egui::ScrollArea::vertical()
+                .id_salt("watch_table")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("watch_grid").striped(true).show(ui, |ui| {
+                        ui.strong(t!("ui.album"));
+                        ui.strong(t!("ui.every"));
+                        ui.strong(t!("ui.last_checked"));
+                        ui.strong(t!("ui.last_result"));
+                        ui.label("");
+                        ui.end_row();
+
+                        for album in &list.albums {
+                            ui.hyperlink_to(&album.url, &album.url).on_hover_text(&album.base_dir);
+                            ui.label(t!("ui.every_minutes", minutes = album.interval_minutes));
+                            if album.last_checked == 0 {
+                                ui.label(never_label);
+                            } else {
+                                ui.label(format_date(album.last_checked));
+                            }
+                            match &album.last_error {
+                                Some(e) => ui.label(e),
+                                None => ui.label(format!("+{} / -{}", album.last_added, album.last_removed)),
+                            };
+                            if ui.button(remove_label).clicked() {
+                                removed = Some(album.url.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+        }
+        if let Some(url) = removed {
+            let mut list = self.watch_list.lock().unwrap_or_else(|e| e.into_inner());
+            list.remove(&url);
+            if let Err(e) = list.save() {
+                log_t!("log.save_watch_list_failed", error = e);
+            }
+        }
+        if self.scheduler.is_some() {
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+        }
+    }
+
+    /// 按界面上的设置建立客户端
+    fn client(&self) -> Result<client::BunkrClient, String> {
+        let config = client::ClientConfig {
+            http: self.http_config()?,
+            mirrors: self.mirror_config(),
+            filter: self.source_filter(),
+            max_pages: self.max_pages,
+            page_concurrency: self.analyze_concurrency,
+            download_concurrency: self.download_concurrency,
+            single_file_folder: self.checked_single_file_folder,
+            duplicate_action: self.duplicate_action,
+            ..Default::default()
+        };
+        client::BunkrClient::builder()
+            .config(config)
+            .history(self.history.clone(), self.duplicate_action)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+
+    /// 历史页：搜索下载过的文件，打开所在文件夹
+    fn show_history(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(t!("ui.search"));
+            ui.text_edit_singleline(&mut self.history_query);
+        });
+
+        let Ok(history) = self.history.try_lock() else {
+            ui.label(t!("ui.history_updating"));
+            return;
+        };
+        let found = history.search(&self.history_query);
+        ui.label(t!("ui.history_files", found = found.len(), total = history.entries().len()));
+
+        let open_label = t!("ui.open_folder");
+        egui::ScrollArea::vertical()
+            .id_salt("history_table")
+            .show(ui, |ui| {
+                egui::Grid::new("history_grid").striped(true).show(ui, |ui| {
+                    ui.strong(t!("ui.date"));
+                    ui.strong(t!("ui.name"));
+                    ui.strong(t!("ui.size"));
+                    ui.strong(t!("ui.album"));
+                    ui.label("");
+                    ui.end_row();
+
+                    for entry in found.into_iter().take(HISTORY_ROWS) {
+                        ui.label(format_date(entry.downloaded_at));
+                        ui.label(&entry.title).on_hover_text(entry.path.to_string_lossy());
+                        ui.label(media::format_size(entry.size));
+                        ui.hyperlink_to(history::file_id(&entry.album_url), &entry.album_url);
+                        if ui.button(open_label).clicked()
+                            && let Some(dir) = entry.path.parent()
+                            && let Err(e) = open::that(dir) {
+                            log_t!("log.open_failed", path = dir.display(), error = e);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    fn duplicate_action_text(&self, action: history::DuplicateAction) -> &'static str {
+        match action {
+            history::DuplicateAction::Download => t!("ui.duplicate_download"),
+            history::DuplicateAction::Skip => t!("ui.duplicate_skip"),
+            history::DuplicateAction::Hardlink => t!("ui.duplicate_hardlink"),
+        }
+    }
+
+    /// 选择一个专辑文件夹，按其中的 manifest.json 重新计算哈希并输出结果
+    fn verify_folder(&self) {
+        let Some(dir) = FileDialog::new()
+            .set_title(t!("ui.select_verify_folder"))
+            .set_directory(&self.base_dir)
+            .pick_folder()
+        else {
+            return;
+        };
+
+        self.controller.verify(dir);
+    }
+
+    /// 弹出保存对话框，把分析出的链接按所选格式写入文件
+    fn export_links(&self, status: &controller::JobStatus) {
+        let format = self.export_format;
+        let selected = FileDialog::new()
+            .set_title(t!("ui.export_links"))
+            .set_file_name(status.export_file_name(format))
+            .add_filter(format.label(), &[format.extension()])
+            .save_file();
+
+        if let Some(path) = selected {
+            match std::fs::write(&path, status.export(format)) {
+                Ok(()) => log_t!("log.exported_links", count = status.sources.len(), path = path.display()),
+                Err(e) => log_t!("log.export_failed", error = e),
+            }
+        }
+    }
+
+    fn source_filter(&self) -> filter::SourceFilter {
+        let kb = |value: u64| (value > 0).then_some(value * 1024);
+        let pattern = |text: &str| Some(text.trim().to_string()).filter(|t| !t.is_empty());
+        filter::SourceFilter {
+            include_kinds: self.filter_kinds.clone(),
+            exclude_kinds: self.filter_exclude_kinds.clone(),
+            include_extensions: filter::SourceFilter::parse_extensions(&self.filter_extensions),
+            exclude_extensions: filter::SourceFilter::parse_extensions(&self.filter_exclude_extensions),
+            min_size: kb(self.filter_min_kb),
+            max_size: kb(self.filter_max_kb),
+            name_pattern: pattern(&self.filter_name),
+            exclude_name_pattern: pattern(&self.filter_exclude_name),
+        }
+    }
+
+    fn mirror_config(&self) -> mirrors::MirrorConfig {
+        mirrors::MirrorConfig {
+            domains: mirrors::MirrorConfig::parse_domains(&self.mirror_domains),
+            cdn_domains: mirrors::MirrorConfig::parse_domains(&self.cdn_domains),
+        }
+    }
+
+    /// 根据界面上的网络设置生成 HTTP 配置
+    fn http_config(&self) -> Result<http::HttpConfig, String> {
+        let cookies_file = self.cookies_file.trim();
+        let proxy = self.proxy.trim();
+        Ok(http::HttpConfig {
+            headers: http::HttpConfig::parse_headers(&self.custom_headers)?,
+            cookies_file: (!cookies_file.is_empty()).then(|| cookies_file.into()),
+            proxy: (!proxy.is_empty()).then(|| proxy.to_string()),
+            ..Default::default()
+        })
+    }
+}
+
+/// Unix 秒转为本地时间
+fn format_date(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 打开窗口界面，直到窗口关闭
+pub fn run() -> Result<(), eframe::Error> {
+    let settings = Settings::load();
+    let window_size = settings.window_size.map_or(DEFAULT_WINDOW_SIZE, egui::Vec2::from);
+    let options = eframe::NativeOptions {
+        window_builder: Some(Box::new(move |viewport_builder| {
+            viewport_builder
+            .with_inner_size(window_size)
+        })),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Bunkr Spider @laull",
+        options,
+
+        Box::new(|creation_ctx| 
+            Ok(Box::new(Gui::new(&creation_ctx.egui_ctx, settings)))),
+    )
+}
@@ -0,0 +1,239 @@
+use crate::bunkr::Source;
+use crate::resolve::UrlKind;
+use crate::log_t;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// 下载历史文件名，位于平台数据目录下的 bunkr-spider 中，每行一条 JSON
+pub const HISTORY_FILE: &str = "history.jsonl";
+const APP_DIR: &str = "bunkr-spider";
+
+/// 遇到以前下载过的文件时怎么处理
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateAction {
+    /// 照常重新下载
+    Download,
+    /// 不下载，只记录
+    Skip,
+    /// 在新目录里建立指向已有文件的硬链接（失败时复制）
+    #[default]
+    Hardlink,
+}
+
+impl DuplicateAction {
+    pub const ALL: [DuplicateAction; 3] = [DuplicateAction::Download, DuplicateAction::Skip, DuplicateAction::Hardlink];
+
+    pub fn parse(text: &str) -> Option<DuplicateAction> {
+        DuplicateAction::ALL
+            .into_iter()
+            .find(|action| action.label().eq_ignore_ascii_case(text.trim()))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DuplicateAction::Download => "download",
+            DuplicateAction::Skip => "skip",
+            DuplicateAction::Hardlink => "hardlink",
+        }
+    }
+}
+
+/// 一次成功下载（或复用）的记录
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub album_url: String,
+    pub page_url: String,
+    /// 文件页链接中的 ID（/f/<id>）
+    pub file_id: String,
+    pub media_url: String,
+    pub title: String,
+    pub size: u64,
+    /// 内容的 SHA-256（十六进制）
+    pub sha256: String,
+    pub path: PathBuf,
+    /// 下载时间（Unix 秒）
+    pub downloaded_at: u64,
+}
+
+impl HistoryEntry {
+    /// CDN 上的文件名，换 CDN 节点也不会变
+    pub fn media_name(&self) -> &str {
+        media_name(&self.media_url)
+    }
+
+    /// 文件仍在磁盘上且大小与记录一致
+    pub fn is_intact(&self) -> bool {
+        std::fs::metadata(&self.path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == self.size)
+    }
+}
+
+/// 本地下载历史，启动时整体读入内存，新记录追加到文件末尾
+#[derive(Debug, Default)]
+pub struct History {
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    pub fn default_path() -> Option<PathBuf> {
+        app_data_dir().map(|dir| dir.join(HISTORY_FILE))
+    }
+
+    /// 读取历史文件，文件不存在时返回空历史，无法解析的行会被跳过
+    pub fn open(path: PathBuf) -> Result<History, std::io::Error> {
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(text) => text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(History { path: Some(path), entries })
+    }
+
+    /// 打开默认位置的历史；失败时只在内存中记录本次运行
+    pub fn open_default() -> History {
+        let Some(path) = History::default_path() else {
+            log_t!("log.no_data_dir");
+            return History::default();
+        };
+        match History::open(path.clone()) {
+            Ok(history) => history,
+            Err(e) => {
+                log_t!("log.read_history_failed", path = path.display(), error = e);
+                History::default()
+            }
+        }
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub async fn record(&mut self, entry: HistoryEntry) {
+        if let Some(path) = &self.path
+            && let Err(e) = Self::append(path, &entry).await
+        {
+            log_t!("log.write_history_failed", path = path.display(), error = e);
+        }
+        self.entries.push(entry);
+    }
+
+    async fn append(path: &Path, entry: &HistoryEntry) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path).await?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// 找一份以前下载过、目前仍完整地在磁盘上的同一文件（按文件 ID 或 CDN 文件名）。
+    /// 磁盘上的大小与记录不同（被截断或修改过）的副本不会被复用
+    pub fn find_copy(&self, source: &Source) -> Option<&HistoryEntry> {
+        let file_id = file_id(&source.page_url);
+        let media = media_name(&source.url);
+        self.entries.iter().rev().find(|entry| {
+            ((!file_id.is_empty() && entry.file_id == file_id) || (!media.is_empty() && entry.media_name() == media))
+                && source.size.is_none_or(|size| size == entry.size)
+                && entry.is_intact()
+        })
+    }
+
+    /// 链接（专辑或文件页）是否出现在历史中。按专辑 slug 或文件 ID 比较，
+    /// 换了镜像域名、带查询参数或末尾斜杠的链接也能认出
+    pub fn contains_url(&self, url: &str) -> bool {
+        let id = file_id(url);
+        if id.is_empty() {
+            return false;
+        }
+        match UrlKind::of(url) {
+            UrlKind::File => self.entries.iter().any(|entry| entry.file_id == id),
+            UrlKind::Album => self.entries.iter().any(|entry| {
+                UrlKind::of(&entry.album_url) == UrlKind::Album && file_id(&entry.album_url) == id
+            }),
+        }
+    }
+
+    /// 找一份内容相同、目前仍在磁盘上的文件
+    pub fn find_by_hash(&self, sha256: &str, except: &Path) -> Option<&HistoryEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|entry| entry.sha256 == sha256 && entry.path != except && entry.is_intact())
+    }
+
+    /// 按标题、专辑链接、文件 ID 或路径搜索（不区分大小写），新的在前
+    pub fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        let query = query.trim().to_lowercase();
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                query.is_empty()
+                    || entry.title.to_lowercase().contains(&query)
+                    || entry.album_url.to_lowercase().contains(&query)
+                    || entry.file_id.to_lowercase().contains(&query)
+                    || entry.path.to_string_lossy().to_lowercase().contains(&query)
+            })
+            .collect()
+    }
+}
+
+/// 本程序在平台数据目录下的文件夹，如 ~/.local/share/bunkr-spider
+pub fn app_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR))
+}
+
+/// 本程序在平台配置目录下的文件夹，如 ~/.config/bunkr-spider
+pub fn app_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR))
+}
+
+/// 文件页链接最后一段即文件 ID（专辑链接则是专辑 slug），忽略末尾的斜杠
+pub fn file_id(page_url: &str) -> String {
+    url::Url::parse(page_url)
+        .ok()
+        .and_then(|u| u.path_segments().and_then(|mut s| s.rfind(|s| !s.is_empty())).map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn media_name(media_url: &str) -> &str {
+    let path = media_url.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_url_ignores_mirror_query_and_trailing_slash() {
+        let entry = HistoryEntry {
+            album_url: "https://bunkr.cr/a/holiday".to_string(),
+            page_url: "https://bunkr.cr/f/abc".to_string(),
+            file_id: "abc".to_string(),
+            media_url: "https://kebab.bunkr.ru/abc.mp4".to_string(),
+            title: "abc.mp4".to_string(),
+            size: 1,
+            sha256: String::new(),
+            path: PathBuf::from("abc.mp4"),
+            downloaded_at: 0,
+        };
+        let history = History { path: None, entries: vec![entry] };
+
+        assert!(history.contains_url("https://bunkr.si/a/holiday/"));
+        assert!(history.contains_url("https://bunkr.la/a/holiday?page=2"));
+        assert!(history.contains_url("https://bunkr.si/v/abc?x=1"));
+        assert!(!history.contains_url("https://bunkr.cr/a/abc"));
+        assert!(!history.contains_url("https://bunkr.cr/f/other"));
+    }
+}
@@ -0,0 +1,150 @@
+use fake_user_agent::get_firefox_rua;
+use reqwest::cookie::Jar;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, REFERER};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 15;
+const DEFAULT_READ_TIMEOUT_SECS: u64 = 60;
+const DEFAULT_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+const DEFAULT_ACCEPT_LANGUAGE: &str = "en-US,en;q=0.5";
+
+/// 爬取和下载共用的 HTTP 配置
+#[derive(Clone, Debug)]
+pub struct HttpConfig {
+    pub user_agent: String,
+    /// 额外请求头，按 `(name, value)` 追加到默认头之后
+    pub headers: Vec<(String, String)>,
+    /// Netscape 格式的 cookies.txt
+    pub cookies_file: Option<PathBuf>,
+    pub connect_timeout: Duration,
+    /// 两次读取之间的最长等待时间（不是整个请求的超时）
+    pub read_timeout: Duration,
+    /// 所有请求经过的代理，如 `http://127.0.0.1:8080`；None 时使用系统代理环境变量
+    pub proxy: Option<String>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: get_firefox_rua().to_string(),
+            headers: Vec::new(),
+            cookies_file: None,
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            read_timeout: Duration::from_secs(DEFAULT_READ_TIMEOUT_SECS),
+            proxy: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// 解析 "Name: value" 每行一个的请求头文本，空行和 `#` 开头的行会被忽略
+    pub fn parse_headers(text: &str) -> Result<Vec<(String, String)>, String> {
+        let mut headers = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                return Err(format!("invalid header line: {}", line));
+            };
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+        Ok(headers)
+    }
+}
+
+/// 根据配置构建 reqwest 客户端，爬取阶段和下载阶段都使用它
+pub fn build_client(config: &HttpConfig) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static(DEFAULT_ACCEPT));
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static(DEFAULT_ACCEPT_LANGUAGE));
+    for (name, value) in &config.headers {
+        headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+    }
+
+    let jar = Arc::new(Jar::default());
+    if let Some(path) = &config.cookies_file {
+        load_netscape_cookies(path, &jar)?;
+    }
+
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    let client = builder
+        .user_agent(&config.user_agent)
+        .default_headers(headers)
+        .cookie_provider(jar)
+        .connect_timeout(config.connect_timeout)
+        .read_timeout(config.read_timeout)
+        .gzip(true)
+        .brotli(true)
+        .build()?;
+
+    Ok(client)
+}
+
+/// 给请求加上 Referer，CDN 通常要求它指向文件页或专辑页
+pub fn with_referer(request: reqwest::RequestBuilder, referer: &str) -> reqwest::RequestBuilder {
+    if referer.is_empty() {
+        request
+    } else {
+        request.header(REFERER, referer)
+    }
+}
+
+/// 请求一个页面并返回 HTML 文本，非 2xx 状态视为错误
+pub async fn fetch_html(
+    client: &reqwest::Client,
+    url: &str,
+    referer: &str,
+) -> Result<String, reqwest::Error> {
+    with_referer(client.get(url), referer)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await
+}
+
+/// 读取 Netscape cookies.txt 并写入 cookie jar，返回导入的条数
+pub fn load_netscape_cookies(path: &Path, jar: &Jar) -> Result<usize, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut count = 0;
+
+    for line in content.lines() {
+        // curl/浏览器导出时 HttpOnly cookie 以 "#HttpOnly_" 前缀标记
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line).trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 7 {
+            continue;
+        }
+        let (domain, path, secure, name, value) =
+            (fields[0], fields[2], fields[3], fields[5], fields[6]);
+
+        let host = domain.trim_start_matches('.');
+        let scheme = if secure.eq_ignore_ascii_case("TRUE") { "https" } else { "http" };
+        let Ok(url) = format!("{}://{}{}", scheme, host, path).parse::<url::Url>() else {
+            continue;
+        };
+
+        let mut cookie = format!("{}={}; Path={}", name, value, path);
+        if domain.starts_with('.') {
+            cookie += &format!("; Domain={}", host);
+        }
+        jar.add_cookie_str(&cookie, &url);
+        count += 1;
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{LazyLock, RwLock};
+
+/// 界面和日志使用的语言，文本在 locales 目录下的 TOML 目录文件中
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "zh-CN")]
+    SimplifiedChinese,
+    #[serde(rename = "zh-TW")]
+    TraditionalChinese,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::English, Language::SimplifiedChinese, Language::TraditionalChinese];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::SimplifiedChinese => "zh-CN",
+            Language::TraditionalChinese => "zh-TW",
+        }
+    }
+
+    /// 用该语言写的语言名，显示在语言下拉框中
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::SimplifiedChinese => "简体中文",
+            Language::TraditionalChinese => "繁體中文",
+        }
+    }
+
+    fn catalog(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.toml"),
+            Language::SimplifiedChinese => include_str!("../locales/zh-CN.toml"),
+            Language::TraditionalChinese => include_str!("../locales/zh-TW.toml"),
+        }
+    }
+
+    /// 解析 "zh-CN"、"zh_TW.UTF-8"、"zh-Hant-HK"、"en-US" 这样的语言标记，不支持的语言返回 None
+    pub fn from_locale(tag: &str) -> Option<Language> {
+        let tag = tag.split(['.', '@']).next().unwrap_or_default().replace('_', "-").to_lowercase();
+        let mut parts = tag.split('-');
+        match parts.next()? {
+            "en" => Some(Language::English),
+            "zh" => {
+                let traditional = parts.any(|part| matches!(part, "hant" | "tw" | "hk" | "mo"));
+                Some(if traditional { Language::TraditionalChinese } else { Language::SimplifiedChinese })
+            }
+            _ => None,
+        }
+    }
+
+    /// 按系统的首选语言依次匹配，都不支持时使用英文
+    pub fn detect() -> Language {
+        sys_locale::get_locales()
+            .find_map(|locale| Language::from_locale(&locale))
+            .unwrap_or_default()
+    }
+}
+
+static CURRENT: LazyLock<RwLock<Language>> = LazyLock::new(|| RwLock::new(Language::detect()));
+
+/// 所有语言的目录，键形如 "ui.run"、"log.album_title"
+static CATALOGS: LazyLock<HashMap<Language, HashMap<String, String>>> = LazyLock::new(|| {
+    Language::ALL
+        .into_iter()
+        .map(|language| {
+            let entries = parse_catalog(language.catalog()).unwrap_or_else(|e| {
+                panic!("locales/{}.toml is not a valid catalog: {}", language.code(), e)
+            });
+            (language, entries)
+        })
+        .collect()
+});
+
+/// 当前语言，没有设置过时按系统语言检测
+pub fn language() -> Language {
+    *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_language(language: Language) {
+    *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = language;
+}
+
+/// 按当前语言查找文本，缺少时依次使用英文和键本身
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(language(), key)
+        .or_else(|| lookup(Language::English, key))
+        .unwrap_or(key)
+}
+
+/// 把文本中的 `{name}` 替换为对应的参数，没有对应参数的占位符原样保留
+pub fn format(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder
+            .find('}')
+            .and_then(|end| args.iter().find(|(name, _)| *name == &placeholder[1..end]).map(|arg| (end, arg.1)));
+        match value {
+            Some((end, value)) => {
+                result.push_str(&value.to_string());
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                result.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn lookup(language: Language, key: &str) -> Option<&'static str> {
+    CATALOGS.get(&language)?.get(key).map(String::as_str)
+}
+
+/// 目录文件的每个表是一组文本，表名作为键的前缀
+fn parse_catalog(text: &str) -> Result<HashMap<String, String>, String> {
+    let table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+    let mut entries = HashMap::new();
+    for (section, values) in table {
+        let values = values.as_table().ok_or_else(|| format!("{} is not a table", section))?;
+        for (key, value) in values {
+            let value = value.as_str().ok_or_else(|| format!("{}.{} is not a string", section, key))?;
+            entries.insert(format!("{}.{}", section, key), value.to_string());
+        }
+    }
+    Ok(entries)
+}
+
+/// 按当前语言取文本：`t!("ui.run")`，带参数时 `t!("log.album_title", title = title)` 返回填好的 String
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::format($crate::i18n::tr($key), &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+])
+    };
+}
+
+/// 按当前语言写一行日志：`log_t!("log.album_title", title = title)`
+#[macro_export]
+macro_rules! log_t {
+    ($($arg:tt)*) => {
+        $crate::log_println!("{}", $crate::t!($($arg)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn catalogs_match_english_keys_and_placeholders() {
+        let english = &CATALOGS[&Language::English];
+        for language in Language::ALL {
+            let catalog = &CATALOGS[&language];
+            for (key, text) in catalog {
+                let Some(english_text) = english.get(key) else {
+                    panic!("{}: {} is not in en.toml", language.code(), key);
+                };
+                assert_eq!(placeholders(text), placeholders(english_text), "{}: {}", language.code(), key);
+            }
+            for key in english.keys() {
+                assert!(catalog.contains_key(key), "{}: missing {}", language.code(), key);
+            }
+        }
+    }
+}
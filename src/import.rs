@@ -0,0 +1,115 @@
+use crate::mirrors::MirrorConfig;
+
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// 从任意文本（txt 文件、剪贴板、拖入的内容）中找出链接的正则
+fn url_regex() -> &'static Regex {
+    static URL_REGEX: OnceLock<Regex> = OnceLock::new();
+    URL_REGEX.get_or_init(|| Regex::new(r#"https?://[^\s"'<>()\[\]{}]+"#).unwrap())
+}
+
+/// 是否是 bunkr 的专辑或文件链接：域名属于已知镜像（或含 bunkr），
+/// 路径形如 /a/xxx、/f/xxx、/v/xxx、/i/xxx
+pub fn is_bunkr_url(url: &str, mirrors: &MirrorConfig) -> bool {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return false;
+    };
+    let Some(host) = parsed.host_str() else {
+        return false;
+    };
+    if !mirrors.is_mirror_host(host) && !host.contains("bunkr") {
+        return false;
+    }
+
+    let mut segments = parsed.path_segments().into_iter().flatten();
+    matches!(segments.next(), Some("a" | "f" | "v" | "i"))
+        && segments.next().is_some_and(|id| !id.is_empty())
+}
+
+/// 提取文本中所有 bunkr 链接，去掉末尾标点和重复项，保持原有顺序
+pub fn extract_urls(text: &str, mirrors: &MirrorConfig) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for found in url_regex().find_iter(text) {
+        let url = found.as_str().trim_end_matches(['.', ',', ';', '!', '?']);
+        if is_bunkr_url(url, mirrors) && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// 读取文本文件并提取其中的链接
+pub fn read_url_file(path: &Path, mirrors: &MirrorConfig) -> Result<Vec<String>, std::io::Error> {
+    let bytes = std::fs::read(path)?;
+    Ok(extract_urls(&String::from_utf8_lossy(&bytes), mirrors))
+}
+
+/// 等待运行的专辑/文件链接，按加入顺序排列且不重复
+#[derive(Clone, Debug, Default)]
+pub struct JobQueue {
+    urls: Vec<String>,
+    /// 本次运行中加入过队列的所有链接，包括已运行或被移除的
+    history: HashSet<String>,
+}
+
+impl JobQueue {
+    /// 加入新链接，已在队列中的忽略，返回实际加入的数量
+    pub fn add(&mut self, urls: impl IntoIterator<Item = String>) -> usize {
+        let before = self.urls.len();
+        for url in urls {
+            if !self.contains(&url) {
+                self.history.insert(url.clone());
+                self.urls.push(url);
+            }
+        }
+        self.urls.len() - before
+    }
+
+    /// 只加入从未进过队列的链接（自动收集时使用），返回实际加入的链接
+    pub fn add_unseen(&mut self, urls: impl IntoIterator<Item = String>) -> Vec<String> {
+        let unseen: Vec<String> = urls.into_iter().filter(|url| !self.history.contains(url)).collect();
+        self.add(unseen.clone());
+        unseen
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.urls.iter().any(|u| u == url)
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.urls.len() {
+            self.urls.remove(index);
+        }
+    }
+
+    pub fn remove_url(&mut self, url: &str) {
+        self.urls.retain(|u| u != url);
+    }
+
+    pub fn pop_front(&mut self) -> Option<String> {
+        (!self.urls.is_empty()).then(|| self.urls.remove(0))
+    }
+
+    pub fn drain(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.urls)
+    }
+
+    pub fn clear(&mut self) {
+        self.urls.clear();
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+}
@@ -0,0 +1,37 @@
+//! Bunkr 专辑爬虫的核心库：解析专辑和文件页、解析媒体直链、并发下载，
+//! 不依赖任何界面库。窗口界面和命令行只是在此之上的前端。
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! use futures::StreamExt;
+//! use rust_spider::client::{BunkrClient, DownloadOptions, Event};
+//!
+//! let client = BunkrClient::builder().page_concurrency(4).build()?;
+//! let album = client.resolve_album("https://bunkr.cr/a/xxxx").await?;
+//! let mut events = client.download(album, DownloadOptions::new("./downloads"));
+//! while let Some(event) = events.next().await {
+//!     if let Event::Finished { downloaded, failed } = event {
+//!         println!("{} downloaded, {} failed", downloaded, failed);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod bunkr;
+pub mod client;
+pub mod config;
+pub mod controller;
+pub mod export;
+pub mod filter;
+pub mod history;
+pub mod i18n;
+pub mod http;
+pub mod import;
+pub mod logger;
+pub mod manifest;
+pub mod media;
+pub mod mirrors;
+pub mod resolve;
+pub mod verify;
+pub mod watch;
@@ -0,0 +1,86 @@
+use std::fmt::{self, Write};
+
+/// 缓冲区的上限（字节），超过后丢掉最早的若干行，长时间运行也不会一直占用内存
+pub const MAX_BUFFER_LEN: usize = 256 * 1024;
+
+/// 全局日志缓冲区：爬虫各阶段的输出都写到这里，界面或命令行再决定如何展示
+#[derive(Default)]
+pub struct Logger {
+    buffer: String,
+    /// 命令行模式下直接输出到 stdout，不再缓冲
+    echo_stdout: bool,
+}
+
+impl Logger {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_echo_stdout(&mut self, echo: bool) {
+        self.echo_stdout = echo;
+    }
+
+    pub fn print(&mut self, args: fmt::Arguments) {
+        if self.echo_stdout {
+            print!("{}", args);
+            return;
+        }
+        let _ = write!(self.buffer, "{}", args);
+        self.trim();
+    }
+
+    pub fn println(&mut self, args: fmt::Arguments) {
+        if self.echo_stdout {
+            println!("{}", args);
+            return;
+        }
+        let _ = writeln!(self.buffer, "{}", args);
+        self.trim();
+    }
+
+    /// 超过上限时按整行丢掉最早的内容，留下约四分之三
+    fn trim(&mut self) {
+        if self.buffer.len() <= MAX_BUFFER_LEN {
+            return;
+        }
+        let excess = self.buffer.len() - MAX_BUFFER_LEN * 3 / 4;
+        let cut = self.buffer.as_bytes()[excess..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .map_or(self.buffer.len(), |newline| excess + newline + 1);
+        self.buffer.drain(..cut);
+    }
+
+    /// 目前缓冲的文本（最多 `MAX_BUFFER_LEN` 字节）
+    pub fn text(&self) -> &str {
+        &self.buffer
+    }
+
+    /// 清空打印缓冲区
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
+
+#[macro_export]
+macro_rules! log_print {
+    ($($arg:tt)*) => {
+        $crate::logger::get_logger().lock().unwrap_or_else(|e| e.into_inner()).print(format_args!($($arg)*))
+    };
+}
+
+
+#[macro_export]
+macro_rules! log_println {
+    ($($arg:tt)*) => {
+        $crate::logger::get_logger().lock().unwrap_or_else(|e| e.into_inner()).println(format_args!($($arg)*))
+    };
+}
+
+pub static LOGGER: std::sync::OnceLock<std::sync::Mutex<Logger>> = std::sync::OnceLock::new();
+
+pub fn get_logger() -> &'static std::sync::Mutex<Logger> {
+    // 首次调用自动初始化，后续直接返回实例（OnceLock 保证仅初始化一次）
+    LOGGER.get_or_init(|| std::sync::Mutex::new(Logger::new()))
+}
@@ -0,0 +1,98 @@
+use crate::filter::SourceFilter;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+/// 每个专辑目录下记录下载结果的文件名
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    Downloaded,
+    /// 以前下载过，按设置跳过，文件在 `ManifestEntry::path` 处而不在专辑目录中
+    Skipped,
+    Failed,
+}
+
+impl EntryStatus {
+    /// 文件已在磁盘上（下载到专辑目录或跳过并沿用已有文件）
+    pub fn is_done(&self) -> bool {
+        matches!(self, EntryStatus::Downloaded | EntryStatus::Skipped)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub title: String,
+    pub file_name: String,
+    pub url: String,
+    pub page_url: String,
+    pub status: EntryStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// 下载时边写边算的 SHA-256（十六进制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 跳过时沿用的已有文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl ManifestEntry {
+    /// 文件在磁盘上的位置：跳过的文件是沿用的那一份，其余在专辑目录 `dir` 中
+    pub fn location(&self, dir: &Path) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| dir.join(&self.file_name))
+    }
+}
+
+/// 专辑目录中的 manifest.json
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub album_url: String,
+    pub title: String,
+    /// 最后一次写入的时间（Unix 秒）
+    pub updated_at: u64,
+    /// 本次下载使用的筛选条件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<SourceFilter>,
+    /// 被筛选条件排除的文件数
+    #[serde(default)]
+    pub filtered_out: usize,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    pub fn new(album_url: &str, title: &str, entries: Vec<ManifestEntry>) -> Manifest {
+        Manifest {
+            album_url: album_url.to_string(),
+            title: title.to_string(),
+            updated_at: unix_now(),
+            filter: None,
+            filtered_out: 0,
+            entries,
+        }
+    }
+
+    pub async fn load(dir: &Path) -> Result<Manifest, Box<dyn std::error::Error>> {
+        let data = fs::read(dir.join(MANIFEST_FILE)).await?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    pub async fn save(&self, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let data = serde_json::to_vec_pretty(self)?;
+        fs::write(dir.join(MANIFEST_FILE), data).await?;
+        Ok(())
+    }
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+const IMAGE_EXTENSIONS: [&str; 14] = [
+    "jpg", "jpeg", "jfif", "png", "gif", "webp", "bmp", "avif", "heic", "heif", "tif", "tiff", "svg", "ico",
+];
+const VIDEO_EXTENSIONS: [&str; 14] = [
+    "mp4", "mkv", "mov", "webm", "avi", "m4v", "wmv", "flv", "mpg", "mpeg", "ts", "m2ts", "3gp", "ogv",
+];
+const AUDIO_EXTENSIONS: [&str; 9] = ["mp3", "wav", "flac", "m4a", "aac", "ogg", "oga", "opus", "wma"];
+const ARCHIVE_EXTENSIONS: [&str; 10] = ["zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz", "zst", "iso"];
+const DOCUMENT_EXTENSIONS: [&str; 13] = [
+    "pdf", "txt", "md", "rtf", "doc", "docx", "xls", "xlsx", "csv", "ppt", "pptx", "epub", "odt",
+];
+
+/// 按文件扩展名（不区分大小写）划分的媒体类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Image,
+    Video,
+    Audio,
+    Archive,
+    Document,
+    Other,
+}
+
+impl MediaKind {
+    pub const ALL: [MediaKind; 6] = [
+        MediaKind::Image,
+        MediaKind::Video,
+        MediaKind::Audio,
+        MediaKind::Archive,
+        MediaKind::Document,
+        MediaKind::Other,
+    ];
+
+    pub fn from_extension(extension: &str) -> MediaKind {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        let extension = extension.as_str();
+
+        if IMAGE_EXTENSIONS.contains(&extension) {
+            MediaKind::Image
+        } else if VIDEO_EXTENSIONS.contains(&extension) {
+            MediaKind::Video
+        } else if AUDIO_EXTENSIONS.contains(&extension) {
+            MediaKind::Audio
+        } else if ARCHIVE_EXTENSIONS.contains(&extension) {
+            MediaKind::Archive
+        } else if DOCUMENT_EXTENSIONS.contains(&extension) {
+            MediaKind::Document
+        } else {
+            MediaKind::Other
+        }
+    }
+
+    pub fn from_file_name(name: &str) -> MediaKind {
+        MediaKind::from_extension(extension_of(name))
+    }
+
+    pub fn parse(text: &str) -> Option<MediaKind> {
+        MediaKind::ALL
+            .into_iter()
+            .find(|kind| kind.label().eq_ignore_ascii_case(text.trim()))
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MediaKind::Image => "image",
+            MediaKind::Video => "video",
+            MediaKind::Audio => "audio",
+            MediaKind::Archive => "archive",
+            MediaKind::Document => "document",
+            MediaKind::Other => "other",
+        }
+    }
+
+    /// 文件页是否有可用来推测下载地址的缩略图（压缩包、文档等只有通用图标）
+    pub fn has_thumbnail(&self) -> bool {
+        matches!(self, MediaKind::Image | MediaKind::Video)
+    }
+}
+
+/// 文件名中最后一个点之后的部分，没有扩展名时返回空串
+pub fn extension_of(name: &str) -> &str {
+    name.rsplit_once('.').map_or("", |(_, ext)| ext)
+}
+
+/// 人类可读的文件大小
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,127 @@
+use crate::log_t;
+
+/// 已知的 bunkr 站点域名，按优先级排列
+pub const DEFAULT_MIRRORS: [&str; 12] = [
+    "bunkr.cr", "bunkr.si", "bunkr.la", "bunkr.is", "bunkr.ru", "bunkr.su",
+    "bunkr.ph", "bunkr.ps", "bunkr.fi", "bunkr.ac", "bunkr.red", "bunkrr.su",
+];
+
+/// 已知的 CDN 根域名，CDN 主机名形如 `<节点>.<根域名>`
+pub const DEFAULT_CDN_DOMAINS: [&str; 3] = ["bunkr.ru", "bunkr.su", "bunkrr.ru"];
+
+#[derive(Clone, Debug)]
+pub struct MirrorConfig {
+    pub domains: Vec<String>,
+    pub cdn_domains: Vec<String>,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self {
+            domains: DEFAULT_MIRRORS.iter().map(|d| d.to_string()).collect(),
+            cdn_domains: DEFAULT_CDN_DOMAINS.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+}
+
+impl MirrorConfig {
+    /// 解析逗号、空白或换行分隔的域名列表
+    pub fn parse_domains(text: &str) -> Vec<String> {
+        text.split(|c: char| c == ',' || c.is_whitespace())
+            .map(|d| d.trim().trim_start_matches("https://").trim_end_matches('/').to_lowercase())
+            .filter(|d| !d.is_empty())
+            .collect()
+    }
+
+    /// 主机名是否属于某个已知镜像（含子域名）
+    pub fn is_mirror_host(&self, host: &str) -> bool {
+        let host = host.trim_start_matches("www.");
+        self.domains
+            .iter()
+            .any(|d| host == d || host.ends_with(&format!(".{}", d)))
+    }
+}
+
+/// 把专辑/文件链接换到一个能访问的镜像上。
+/// 原域名可用时保持不变；都不可用时返回原链接，交给后续流程报错。
+pub async fn normalize_url(client: &reqwest::Client, url: &str, config: &MirrorConfig) -> String {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(host) = parsed.host_str().map(str::to_string) else {
+        return url.to_string();
+    };
+    if !config.is_mirror_host(&host) {
+        return url.to_string();
+    }
+
+    if is_reachable(client, url).await {
+        return url.to_string();
+    }
+    log_t!("log.host_unreachable", host = host);
+
+    for domain in &config.domains {
+        if *domain == host {
+            continue;
+        }
+        let mut candidate = parsed.clone();
+        if candidate.set_host(Some(domain)).is_err() {
+            continue;
+        }
+        if is_reachable(client, candidate.as_str()).await {
+            log_t!("log.using_mirror", domain = domain);
+            return candidate.to_string();
+        }
+    }
+
+    log_t!("log.no_mirror", url = url);
+    url.to_string()
+}
+
+/// 为媒体链接生成其他 CDN 主机名的候选链接（保留节点名，替换根域名）。
+/// 只在 CDN 根域名之间切换，站点镜像不提供媒体文件
+pub fn cdn_alternatives(url: &str, config: &MirrorConfig) -> Vec<String> {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return Vec::new();
+    };
+    let Some(host) = parsed.host_str() else {
+        return Vec::new();
+    };
+
+    let root = config.cdn_domains.iter().find(|d| host.ends_with(&format!(".{}", d)));
+    let Some(root) = root else {
+        return Vec::new();
+    };
+    let node = &host[..host.len() - root.len() - 1];
+
+    let mut alternatives = Vec::new();
+    for domain in &config.cdn_domains {
+        if domain == root {
+            continue;
+        }
+        let mut candidate = parsed.clone();
+        if candidate.set_host(Some(&format!("{}.{}", node, domain))).is_ok() {
+            let candidate = candidate.to_string();
+            if !alternatives.contains(&candidate) {
+                alternatives.push(candidate);
+            }
+        }
+    }
+    alternatives
+}
+
+/// DNS 解析失败、连接失败或超时，这类错误换个主机名可能就好了
+pub fn is_connection_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+}
+
+/// 只有 DNS 解析、连接失败或超时才算主机不可用；404、403 等说明主机在线，
+/// 换镜像也没有用，留给后续请求报错
+async fn is_reachable(client: &reqwest::Client, url: &str) -> bool {
+    match client.get(url).send().await {
+        Ok(_) => true,
+        Err(e) => !is_connection_error(&e),
+    }
+}
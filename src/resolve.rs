@@ -0,0 +1,367 @@
+use crate::http;
+use crate::media::MediaKind;
+
+use base64::Engine;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+
+const VS_ENDPOINT: &str = "/api/vs";
+
+type ResolveError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 输入链接的类型，由路径前缀判断：/a/ 是专辑，/f/、/v/、/i/ 是单个文件
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UrlKind {
+    Album,
+    File,
+}
+
+impl UrlKind {
+    pub fn of(url: &str) -> UrlKind {
+        let first_segment = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.path_segments().and_then(|mut s| s.next()).map(str::to_string))
+            .unwrap_or_default();
+
+        match first_segment.as_str() {
+            "f" | "v" | "i" => UrlKind::File,
+            _ => UrlKind::Album,
+        }
+    }
+}
+
+/// 媒体链接是通过哪种方式得到的
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    /// 文件页主图 `<img>`
+    Image,
+    /// `<video>` / `<audio>` / `<source>` 的 src
+    MediaSource,
+    /// 下载按钮直接指向媒体文件
+    DownloadLink,
+    /// 站点下载接口返回的链接
+    Endpoint,
+    /// 从缩略图地址推测，可能不准确
+    ThumbnailGuess,
+}
+
+impl Resolution {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Resolution::Image => "image",
+            Resolution::MediaSource => "media_source",
+            Resolution::DownloadLink => "download_link",
+            Resolution::Endpoint => "endpoint",
+            Resolution::ThumbnailGuess => "thumbnail_guess",
+        }
+    }
+}
+
+/// 从文件页（/f/、/v/、/i/）HTML 中提取出的信息
+#[derive(Clone, Debug, Default)]
+pub struct FilePage {
+    pub title: String,
+    pub thumbnail: Option<String>,
+    pub image_src: Option<String>,
+    pub video_src: Option<String>,
+    pub audio_src: Option<String>,
+    pub download_href: Option<String>,
+}
+
+impl FilePage {
+    pub fn parse(html: &str, page_url: &str) -> FilePage {
+        let document = Html::parse_document(html);
+        let base = url::Url::parse(page_url).ok();
+
+        let meta = |property: &str| {
+            let selector = Selector::parse(&format!("meta[property=\"{}\"]", property)).unwrap();
+            document
+                .select(&selector)
+                .next()
+                .and_then(|e| e.value().attr("content"))
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+        };
+        let attr = |selector: &str, name: &str| {
+            let selector = Selector::parse(selector).unwrap();
+            document
+                .select(&selector)
+                .filter_map(|e| e.value().attr(name))
+                .map(str::trim)
+                .find(|v| !v.is_empty())
+                .map(|v| absolute_url(base.as_ref(), v))
+        };
+
+        let download_selector = Selector::parse("a[href]").unwrap();
+        let download_href = document
+            .select(&download_selector)
+            .find(|e| e.text().any(|t| t.trim().eq_ignore_ascii_case("download")))
+            .and_then(|e| e.value().attr("href"))
+            .map(|href| absolute_url(base.as_ref(), href.trim()));
+
+        FilePage {
+            title: meta("og:title").unwrap_or_default(),
+            thumbnail: meta("og:image"),
+            image_src: attr("figure > img.max-h-full.w-auto.object-cover.relative.z-20", "src"),
+            video_src: attr("video source[src]", "src").or_else(|| attr("video[src]", "src")),
+            audio_src: attr("audio source[src]", "src").or_else(|| attr("audio[src]", "src")),
+            download_href,
+        }
+    }
+
+    /// 标题中的扩展名（小写，不含点）
+    pub fn extension(&self) -> String {
+        match self.title.rsplit_once('.') {
+            Some((_, ext)) => ext.to_lowercase(),
+            None => String::new(),
+        }
+    }
+
+    pub fn kind(&self) -> MediaKind {
+        MediaKind::from_extension(&self.extension())
+    }
+}
+
+/// 专辑页中提取出的信息：标题、文件页链接和分页链接
+#[derive(Clone, Debug, Default)]
+pub struct AlbumPage {
+    pub title: String,
+    pub items: Vec<String>,
+    pub pages: Vec<String>,
+}
+
+impl AlbumPage {
+    pub fn parse(html: &str, page_url: &str) -> AlbumPage {
+        let document = Html::parse_document(html);
+        let Ok(base) = url::Url::parse(page_url) else {
+            return AlbumPage::default();
+        };
+
+        let title_selector = Selector::parse("meta[property=\"og:title\"]").unwrap();
+        let title = document
+            .select(&title_selector)
+            .next()
+            .and_then(|e| e.value().attr("content"))
+            .map(|c| c.trim().to_string())
+            .unwrap_or_default();
+
+        let link_selector = Selector::parse("a[href]").unwrap();
+        let mut items = Vec::new();
+        let mut pages = Vec::new();
+        for href in document.select(&link_selector).filter_map(|e| e.value().attr("href")) {
+            let Ok(mut link) = base.join(href.trim()) else {
+                continue;
+            };
+            link.set_fragment(None);
+
+            if UrlKind::of(link.as_str()) == UrlKind::File {
+                link.set_query(None);
+                if !items.contains(&link.to_string()) {
+                    items.push(link.to_string());
+                }
+            } else if link.host_str() == base.host_str()
+                && link.path() == base.path()
+                && link.query_pairs().any(|(k, _)| k == "page")
+                && link != base
+                && !pages.contains(&link.to_string())
+            {
+                pages.push(link.to_string());
+            }
+        }
+
+        AlbumPage { title, items, pages }
+    }
+}
+
+/// 按类型和可靠程度依次尝试：图片取主图、音视频取播放器地址，
+/// 然后是下载按钮、下载接口，最后才对有缩略图的类型用缩略图推测
+pub async fn resolve_media(
+    client: &reqwest::Client,
+    page: &FilePage,
+    page_url: &str,
+) -> Option<(String, Resolution)> {
+    let kind = page.kind();
+
+    let embedded = match kind {
+        MediaKind::Image => page.image_src.as_ref().map(|src| (src, Resolution::Image)),
+        MediaKind::Video => page.video_src.as_ref().map(|src| (src, Resolution::MediaSource)),
+        MediaKind::Audio => page.audio_src.as_ref().map(|src| (src, Resolution::MediaSource)),
+        MediaKind::Archive | MediaKind::Document | MediaKind::Other => None,
+    };
+    if let Some((src, resolution)) = embedded {
+        return Some((src.clone(), resolution));
+    }
+
+    if let Some(href) = &page.download_href
+        && is_direct_link(href, &page.extension())
+    {
+        return Some((href.clone(), Resolution::DownloadLink));
+    }
+
+    if let Ok(link) = resolve_via_endpoint(client, page_url).await {
+        return Some((link, Resolution::Endpoint));
+    }
+
+    if !kind.has_thumbnail() {
+        return None;
+    }
+    page.thumbnail
+        .as_ref()
+        .map(|thumb| (guess_from_thumbnail(thumb, &page.extension()), Resolution::ThumbnailGuess))
+}
+
+#[derive(Deserialize)]
+struct VsResponse {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    timestamp: u64,
+    url: String,
+}
+
+/// 调用站点的下载接口，用文件页 slug 换取真实下载地址
+pub async fn resolve_via_endpoint(client: &reqwest::Client, page_url: &str) -> Result<String, ResolveError> {
+    let page = url::Url::parse(page_url)?;
+    let slug = page
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|s| !s.is_empty())
+        .ok_or("file page url has no slug")?;
+    let endpoint = page.join(VS_ENDPOINT)?;
+
+    let response: VsResponse = http::with_referer(client.post(endpoint), page_url)
+        .json(&serde_json::json!({ "slug": slug }))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if !response.encrypted {
+        return Ok(response.url);
+    }
+    decrypt_url(&response.url, response.timestamp)
+}
+
+/// 接口返回的地址按小时密钥做了 XOR，再 base64 编码
+fn decrypt_url(encoded: &str, timestamp: u64) -> Result<String, ResolveError> {
+    let key = format!("SECRET_KEY_{}", timestamp / 3600);
+    let data = base64::engine::general_purpose::STANDARD.decode(encoded.as_bytes())?;
+    let decoded: Vec<u8> = data
+        .iter()
+        .zip(key.as_bytes().iter().cycle())
+        .map(|(b, k)| b ^ k)
+        .collect();
+
+    Ok(String::from_utf8(decoded)?)
+}
+
+/// 旧的缩略图推测：去掉 /thumbs 和 i- 前缀的 CDN，再换成标题里的扩展名
+pub fn guess_from_thumbnail(thumbnail: &str, extension: &str) -> String {
+    let mut link = remove_all_extensions_after_last_slash(
+        thumbnail
+            .replace("/thumbs", "")
+            .replace("https://i-", "https://"),
+    );
+    if !extension.is_empty() {
+        link += ".";
+        link += extension;
+    }
+    link
+}
+
+fn is_direct_link(href: &str, extension: &str) -> bool {
+    let Ok(url) = url::Url::parse(href) else {
+        return false;
+    };
+    !extension.is_empty() && url.path().to_lowercase().ends_with(&format!(".{}", extension))
+}
+
+fn absolute_url(base: Option<&url::Url>, link: &str) -> String {
+    base.and_then(|b| b.join(link).ok())
+        .map(|u| u.to_string())
+        .unwrap_or_else(|| link.to_string())
+}
+
+fn remove_all_extensions_after_last_slash(url: String) -> String {
+    if let Some(last_slash) = url.rfind('/') {
+        let (base, filename) = url.split_at(last_slash + 1);
+
+        if let Some(first_dot) = filename.find('.') {
+            format!("{}{}", base, &filename[..first_dot])
+        } else {
+            url.to_string()
+        }
+    } else if let Some(first_dot) = url.find('.') {
+        url[..first_dot].to_string()
+    } else {
+        url.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE_PAGE: &str = r#"<html><head>
+        <meta property="og:title" content=" clip.MP4 ">
+        <meta property="og:image" content="https://i-kebab.bunkr.ru/thumbs/abc-123.png">
+        </head><body>
+        <video><source src="https://kebab.bunkr.ru/abc-123.mp4"></video>
+        <a href="/d/abc">Download</a>
+        </body></html>"#;
+
+    const ALBUM_PAGE: &str = r#"<html><head><meta property="og:title" content="Holiday"></head><body>
+        <a href="/f/one">one</a>
+        <a href="https://bunkr.cr/f/one?x=1#top">one again</a>
+        <a href="/v/two">two</a>
+        <a href="/a/holiday?page=2">2</a>
+        <a href="/a/holiday?page=2#list">2 again</a>
+        <a href="/a/other?page=2">elsewhere</a>
+        <a href="/a/holiday">first</a>
+        </body></html>"#;
+
+    #[test]
+    fn parses_file_page() {
+        let page = FilePage::parse(FILE_PAGE, "https://bunkr.cr/f/abc");
+        assert_eq!(page.title, "clip.MP4");
+        assert_eq!(page.extension(), "mp4");
+        assert_eq!(page.kind(), MediaKind::Video);
+        assert_eq!(page.video_src.as_deref(), Some("https://kebab.bunkr.ru/abc-123.mp4"));
+        assert_eq!(page.download_href.as_deref(), Some("https://bunkr.cr/d/abc"));
+        assert_eq!(page.image_src, None);
+    }
+
+    #[test]
+    fn parses_album_items_and_pages() {
+        let page = AlbumPage::parse(ALBUM_PAGE, "https://bunkr.cr/a/holiday");
+        assert_eq!(page.title, "Holiday");
+        assert_eq!(page.items, ["https://bunkr.cr/f/one", "https://bunkr.cr/v/two"]);
+        assert_eq!(page.pages, ["https://bunkr.cr/a/holiday?page=2"]);
+    }
+
+    #[test]
+    fn decrypts_endpoint_url() {
+        let timestamp = 7200 * 3 + 5;
+        let key = format!("SECRET_KEY_{}", timestamp / 3600);
+        let plain = "https://kebab.bunkr.ru/abc-123.mp4";
+        let xored: Vec<u8> = plain.bytes().zip(key.bytes().cycle()).map(|(b, k)| b ^ k).collect();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(xored);
+
+        assert_eq!(decrypt_url(&encoded, timestamp).unwrap(), plain);
+        assert!(decrypt_url("not base64!", timestamp).is_err());
+    }
+
+    #[test]
+    fn guesses_from_thumbnail() {
+        assert_eq!(
+            guess_from_thumbnail("https://i-kebab.bunkr.ru/thumbs/abc-123.png", "mp4"),
+            "https://kebab.bunkr.ru/abc-123.mp4"
+        );
+        assert_eq!(
+            guess_from_thumbnail("https://i-kebab.bunkr.ru/thumbs/abc-123.png", ""),
+            "https://kebab.bunkr.ru/abc-123"
+        );
+    }
+}
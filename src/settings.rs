@@ -0,0 +1,120 @@
+use rust_spider::bunkr;
+use rust_spider::export::ExportFormat;
+use rust_spider::history::{self, DuplicateAction};
+use rust_spider::i18n::Language;
+use rust_spider::media::MediaKind;
+use rust_spider::{log_t, mirrors, watch};
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 界面设置文件名，位于平台配置目录下的 bunkr-spider 中
+pub const SETTINGS_FILE: &str = "gui.json";
+
+/// 窗口界面上所有可调整的选项，启动时读取，修改后自动保存
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub base_dir: String,
+    /// None 表示跟随系统语言
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<Language>,
+    pub delete_invalid: bool,
+    pub single_file_folder: bool,
+    pub pipelined: bool,
+    pub review: bool,
+    pub export_format: ExportFormat,
+    pub duplicate_action: DuplicateAction,
+    pub watch_clipboard: bool,
+    pub auto_watch: bool,
+    pub watch_interval: u64,
+    pub cookies_file: String,
+    /// 为空时使用系统代理
+    pub proxy: String,
+    pub custom_headers: String,
+    pub mirror_domains: String,
+    pub cdn_domains: String,
+    pub max_pages: usize,
+    pub analyze_concurrency: usize,
+    pub download_concurrency: usize,
+    pub retry_concurrency: usize,
+    pub retry_switch_cdn: bool,
+    pub filter_kinds: Vec<MediaKind>,
+    pub filter_exclude_kinds: Vec<MediaKind>,
+    pub filter_extensions: String,
+    pub filter_exclude_extensions: String,
+    pub filter_min_kb: u64,
+    pub filter_max_kb: u64,
+    pub filter_name: String,
+    pub filter_exclude_name: String,
+    /// 窗口大小（逻辑像素），None 时使用默认大小
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_size: Option<[f32; 2]>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            base_dir: String::new(),
+            language: None,
+            delete_invalid: true,
+            single_file_folder: false,
+            pipelined: false,
+            review: false,
+            export_format: ExportFormat::Urls,
+            duplicate_action: DuplicateAction::default(),
+            watch_clipboard: false,
+            auto_watch: false,
+            watch_interval: watch::DEFAULT_INTERVAL_MINUTES,
+            cookies_file: String::new(),
+            proxy: String::new(),
+            custom_headers: String::new(),
+            mirror_domains: mirrors::DEFAULT_MIRRORS.join(", "),
+            cdn_domains: mirrors::DEFAULT_CDN_DOMAINS.join(", "),
+            max_pages: bunkr::DEFAULT_MAX_PAGES,
+            analyze_concurrency: bunkr::DEFAULT_ANALYZE_CONCURRENT_LIMIT,
+            download_concurrency: bunkr::DEFAULT_DOWNLOAD_CONCURRENT_LIMIT,
+            retry_concurrency: 2,
+            retry_switch_cdn: false,
+            filter_kinds: Vec::new(),
+            filter_exclude_kinds: Vec::new(),
+            filter_extensions: String::new(),
+            filter_exclude_extensions: String::new(),
+            filter_min_kb: 0,
+            filter_max_kb: 0,
+            filter_name: String::new(),
+            filter_exclude_name: String::new(),
+            window_size: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn path() -> Option<PathBuf> {
+        history::app_config_dir().map(|dir| dir.join(SETTINGS_FILE))
+    }
+
+    /// 读取设置，文件不存在时使用默认值，无法解析时记录原因并使用默认值
+    pub fn load() -> Settings {
+        let Some(path) = Settings::path() else {
+            return Settings::default();
+        };
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(_) => return Settings::default(),
+        };
+        serde_json::from_slice(&data).unwrap_or_else(|e| {
+            log_t!("log.read_settings_failed", path = path.display(), error = e);
+            Settings::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Settings::path().ok_or("no config directory found")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
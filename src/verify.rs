@@ -0,0 +1,125 @@
+use crate::manifest::{Manifest, MANIFEST_FILE};
+use crate::log_t;
+
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+
+/// 按 manifest 重新检查专辑目录的结果，列表中都是文件名
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub ok: Vec<String>,
+    /// manifest 中已下载（或跳过时沿用的文件）、磁盘上却不存在
+    pub missing: Vec<String>,
+    /// 比记录的小，多半是下载中断
+    pub truncated: Vec<String>,
+    /// 大小或哈希与记录不一致
+    pub modified: Vec<String>,
+    /// 目录中有、manifest 中没有
+    pub extra: Vec<String>,
+    /// 存在但读取失败，附带错误原因
+    pub unreadable: Vec<(String, String)>,
+    /// 旧 manifest 没有记录哈希，只核对了大小
+    pub unhashed: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.truncated.is_empty() && self.modified.is_empty()
+            && self.extra.is_empty()
+            && self.unreadable.is_empty()
+    }
+
+    pub fn print(&self, dir: &Path) {
+        log_t!(
+            "log.verify_summary",
+            path = dir.display(),
+            ok = self.ok.len(),
+            missing = self.missing.len(),
+            truncated = self.truncated.len(),
+            modified = self.modified.len(),
+            extra = self.extra.len()
+        );
+        for name in &self.missing {
+            log_t!("log.verify_missing", name = name);
+        }
+        for name in &self.truncated {
+            log_t!("log.verify_truncated", name = name);
+        }
+        for name in &self.modified {
+            log_t!("log.verify_modified", name = name);
+        }
+        for name in &self.extra {
+            log_t!("log.verify_extra", name = name);
+        }
+        for (name, error) in &self.unreadable {
+            log_t!("log.verify_unreadable", name = name, error = error);
+        }
+        if !self.unhashed.is_empty() {
+            log_t!("log.verify_unhashed", count = self.unhashed.len());
+        }
+    }
+}
+
+/// 读取目录中的 manifest.json，重新计算每个已下载文件的哈希并与记录比较
+pub async fn verify_dir(dir: &Path) -> Result<VerifyReport, Box<dyn std::error::Error>> {
+    let manifest = Manifest::load(dir)
+        .await
+        .map_err(|e| format!("cannot read {} in {}: {}", MANIFEST_FILE, dir.display(), e))?;
+    let mut report = VerifyReport::default();
+
+    for entry in manifest.entries.iter().filter(|e| e.status.is_done()) {
+        let path = entry.location(dir);
+        let Ok(metadata) = fs::metadata(&path).await else {
+            report.missing.push(entry.file_name.clone());
+            continue;
+        };
+
+        let size = metadata.len();
+        match entry.size {
+            Some(expected) if size < expected => report.truncated.push(entry.file_name.clone()),
+            Some(expected) if size != expected => report.modified.push(entry.file_name.clone()),
+            _ => match &entry.sha256 {
+                // 单个文件读不了不影响其余文件的检查
+                Some(expected) => match hash_file(&path).await {
+                    Ok(hash) if hash == *expected => report.ok.push(entry.file_name.clone()),
+                    Ok(_) => report.modified.push(entry.file_name.clone()),
+                    Err(e) => report.unreadable.push((entry.file_name.clone(), e.to_string())),
+                },
+                None => {
+                    report.unhashed.push(entry.file_name.clone());
+                    report.ok.push(entry.file_name.clone());
+                }
+            },
+        }
+    }
+
+    let known: HashSet<&str> = manifest.entries.iter().map(|e| e.file_name.as_str()).collect();
+    let mut entries = fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type().await?.is_file() && name != MANIFEST_FILE && !known.contains(name.as_str()) {
+            report.extra.push(name);
+        }
+    }
+    report.extra.sort();
+
+    Ok(report)
+}
+
+/// 流式计算文件的 SHA-256（十六进制）
+pub async fn hash_file(path: &Path) -> Result<String, std::io::Error> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
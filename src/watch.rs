@@ -0,0 +1,185 @@
+use crate::bunkr::BunkrSpider;
+use crate::client::BunkrClient;
+use crate::controller::Controller;
+use crate::history;
+use crate::manifest::unix_now;
+use crate::log_t;
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 关注列表文件名，与下载历史放在同一目录
+pub const WATCH_FILE: &str = "watched.json";
+pub const DEFAULT_INTERVAL_MINUTES: u64 = 60;
+/// 调度器检查是否有到期专辑的间隔
+pub const TICK: Duration = Duration::from_secs(30);
+
+/// 一个被关注、需要定期同步的专辑
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchedAlbum {
+    pub url: String,
+    /// 专辑文件夹所在的目录
+    pub base_dir: String,
+    pub interval_minutes: u64,
+    /// 上次检查的时间（Unix 秒），0 表示从未检查
+    pub last_checked: u64,
+    pub last_added: usize,
+    pub last_removed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl Default for WatchedAlbum {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            base_dir: ".".to_string(),
+            interval_minutes: DEFAULT_INTERVAL_MINUTES,
+            last_checked: 0,
+            last_added: 0,
+            last_removed: 0,
+            last_error: None,
+        }
+    }
+}
+
+impl WatchedAlbum {
+    pub fn is_due(&self, now: u64) -> bool {
+        now.saturating_sub(self.last_checked) >= self.interval_minutes.max(1) * 60
+    }
+}
+
+/// 关注的专辑列表，保存在平台数据目录的 watched.json
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WatchList {
+    pub albums: Vec<WatchedAlbum>,
+}
+
+impl WatchList {
+    pub fn default_path() -> Option<PathBuf> {
+        history::app_data_dir().map(|dir| dir.join(WATCH_FILE))
+    }
+
+    /// 读取关注列表，文件不存在或无法解析时返回空列表
+    pub fn load() -> WatchList {
+        WatchList::default_path()
+            .and_then(|path| std::fs::read(path).ok())
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = WatchList::default_path().ok_or("no data directory found")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// 加入关注，已关注的链接只更新目录和间隔，返回是否新加入
+    pub fn add(&mut self, url: &str, base_dir: &str, interval_minutes: u64) -> bool {
+        let url = url.trim();
+        if let Some(album) = self.albums.iter_mut().find(|a| a.url == url) {
+            album.base_dir = base_dir.to_string();
+            album.interval_minutes = interval_minutes;
+            return false;
+        }
+        self.albums.push(WatchedAlbum {
+            url: url.to_string(),
+            base_dir: base_dir.to_string(),
+            interval_minutes,
+            ..Default::default()
+        });
+        true
+    }
+
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.albums.len();
+        self.albums.retain(|a| a.url != url.trim());
+        self.albums.len() != before
+    }
+}
+
+/// 同步所有到期（`force` 时为全部）的关注专辑，每个专辑结束后保存列表；
+/// 爬虫被停止时不再检查剩下的专辑
+pub async fn sync_due(list: &Mutex<WatchList>, spider: &mut BunkrSpider, force: bool) {
+    let due: Vec<WatchedAlbum> = {
+        let list = list.lock().unwrap_or_else(|e| e.into_inner());
+        let now = unix_now();
+        list.albums.iter().filter(|a| force || a.is_due(now)).cloned().collect()
+    };
+
+    for album in due {
+        if spider.is_stopped() {
+            return;
+        }
+        log_t!("log.checking_album", url = album.url);
+        spider.reset();
+        let result = spider.sync(album.base_dir.clone(), album.url.clone()).await;
+
+        match &result {
+            Ok(report) if report.added.is_empty() && report.removed.is_empty() => {
+                log_t!("log.no_changes", url = album.url);
+            }
+            Ok(report) => log_t!(
+                "log.album_changes",
+                url = album.url,
+                added = report.added.len(),
+                removed = report.removed.len()
+            ),
+            Err(e) => log_t!("log.check_failed", url = album.url, error = e),
+        }
+
+        let mut list = list.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = list.albums.iter_mut().find(|a| a.url == album.url) {
+            entry.last_checked = unix_now();
+            match result {
+                Ok(report) => {
+                    entry.last_added = report.added.len();
+                    entry.last_removed = report.removed.len();
+                    entry.last_error = None;
+                }
+                Err(e) => entry.last_error = Some(e.to_string()),
+            }
+        }
+        if let Err(e) = list.save() {
+            log_t!("log.save_watch_list_failed", error = e);
+        }
+    }
+}
+
+/// 后台定时同步关注的专辑：有到期的专辑且控制器空闲时交给控制器运行，
+/// 因此可以和其他任务一样暂停、停止，也不会与手动检查同时运行。drop 后不再提交新的同步
+pub struct Scheduler {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Scheduler {
+    /// 在当前 tokio 运行时中轮询，`client` 带有网络、筛选等设置
+    pub fn start(list: Arc<Mutex<WatchList>>, controller: Controller, client: BunkrClient) -> Scheduler {
+        log_t!("log.watch_started");
+        let task = tokio::spawn(async move {
+            loop {
+                let now = unix_now();
+                let due = list.lock().unwrap_or_else(|e| e.into_inner()).albums.iter().any(|a| a.is_due(now));
+                if due && !controller.status().busy {
+                    controller.sync_watched(client.clone(), list.clone(), false);
+                }
+                tokio::time::sleep(TICK).await;
+            }
+        });
+
+        Scheduler { task }
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.task.abort();
+        log_t!("log.watch_stopped");
+    }
+}